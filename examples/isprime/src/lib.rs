@@ -1,25 +1,17 @@
 mod utils;
 
 use num::{BigUint, Zero};
-use std::time::Duration;
 use wasm_bindgen::JsValue;
 use webio::{
     event::{self, EventType},
     time::Instant,
 };
 
-/// Number of steps before yielding control back to browser when testing
-/// whether a number is prime or not, in order not to freeze the browser with
-/// computations on large numbers. Of course, yielding back to the browser is
-/// just a pause, so after a few milliseconds later, WASM can resume its job on
-/// the current number.
-///
-/// However, note that this applies only when a number is being tested,
-/// otherwise WASM sleeps and won't wake up until the button is pressed.
-const YIELD_STEPS: u16 = 20000;
-
 /// Tests if the given number is prime, asynchronous because it will pause the
-/// execution after some steps.
+/// execution whenever it runs for longer than the frame budget, in order not
+/// to freeze the browser with computations on large numbers. Of course,
+/// yielding back to the browser is just a pause, so the browser can render and
+/// handle input, and then WASM resumes its job on the current number.
 async fn is_prime(number: &BigUint) -> bool {
     let two = BigUint::from(2u8);
     if *number < two {
@@ -38,9 +30,7 @@ async fn is_prime(number: &BigUint) -> bool {
         if (number % &attempt).is_zero() {
             return false;
         }
-        if (&attempt / &two % YIELD_STEPS).is_zero() {
-            webio::time::timeout(Duration::from_millis(10)).await;
-        }
+        webio::task::consume_budget().await;
         attempt += &two;
         square = &attempt * &attempt;
     }
@@ -6,4 +6,7 @@ mod shared;
 pub mod once;
 pub mod multi;
 
-pub use shared::Cancelled;
+pub use shared::{BufferPolicy, Cancelled};
+
+#[cfg(feature = "time")]
+pub(crate) use shared::Buffer;
@@ -2,7 +2,7 @@
 //! that are called multiple times, i.e. callbacks of events that occur more
 //! than once per callback.
 
-use crate::callback;
+use crate::callback::{self, BufferPolicy};
 use std::{future::Future, pin::Pin, task};
 
 #[cfg(feature = "stream")]
@@ -10,7 +10,8 @@ use futures::stream::Stream;
 
 macro_rules! sync_multi {
     ($self:expr, $callback:expr) => {{
-        let (notifier, inner_listener) = callback::shared::channel();
+        let (notifier, inner_listener) =
+            callback::shared::buffered_channel($self.policy);
 
         let handler = Box::new(move |event_data| {
             let data = $callback(event_data);
@@ -24,7 +25,8 @@ macro_rules! sync_multi {
 
 macro_rules! async_multi {
     ($self:expr, $callback:expr) => {{
-        let (notifier, inner_listener) = callback::shared::channel();
+        let (notifier, inner_listener) =
+            callback::shared::buffered_channel($self.policy);
 
         let handler = Box::new(move |event_data| {
             let future = $callback(event_data);
@@ -61,6 +63,7 @@ pub type AsyncCbHandler<'cb, 'fut, T> =
 #[derive(Debug, Clone, Copy)]
 pub struct SyncRegister<F> {
     register_fn: F,
+    policy: BufferPolicy,
 }
 
 impl<F> SyncRegister<F> {
@@ -74,7 +77,7 @@ impl<F> SyncRegister<F> {
     where
         F: FnOnce(SyncCbHandler<'cb, T>) -> U,
     {
-        Self { register_fn }
+        Self { register_fn, policy: BufferPolicy::default() }
     }
 
     /// Creates a new register using an inner register function that can be used
@@ -86,7 +89,7 @@ impl<F> SyncRegister<F> {
     where
         F: FnMut(SyncCbHandler<'cb, T>) -> U,
     {
-        Self { register_fn }
+        Self { register_fn, policy: BufferPolicy::default() }
     }
 
     /// Creates a new register using an inner register function that can be used
@@ -98,7 +101,19 @@ impl<F> SyncRegister<F> {
     where
         F: Fn(SyncCbHandler<'cb, T>) -> U,
     {
-        Self { register_fn }
+        Self { register_fn, policy: BufferPolicy::default() }
+    }
+
+    /// Sets the policy of the buffer of listeners created by this register,
+    /// i.e. what happens to occurences of the event that arrive before the
+    /// previous ones are consumed. By default, [`BufferPolicy::CoalesceLatest`]
+    /// is used.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the policy has a capacity of zero.
+    pub fn buffered(self, policy: BufferPolicy) -> Self {
+        Self { policy: policy.validate(), ..self }
     }
 
     /// Registers a callback and lets it listen for the target event. A listener
@@ -283,6 +298,7 @@ impl<F> SyncRegister<F> {
 #[derive(Debug, Clone, Copy)]
 pub struct AsyncRegister<F> {
     register_fn: F,
+    policy: BufferPolicy,
 }
 
 impl<F> AsyncRegister<F> {
@@ -298,7 +314,7 @@ impl<F> AsyncRegister<F> {
         'fut: 'cb,
         F: FnOnce(AsyncCbHandler<'cb, 'fut, T>) -> U,
     {
-        Self { register_fn }
+        Self { register_fn, policy: BufferPolicy::default() }
     }
 
     /// Creates a new register using an inner register function that can be used
@@ -312,7 +328,7 @@ impl<F> AsyncRegister<F> {
         'fut: 'cb,
         F: FnMut(AsyncCbHandler<'cb, 'fut, T>) -> U,
     {
-        Self { register_fn }
+        Self { register_fn, policy: BufferPolicy::default() }
     }
 
     /// Creates a new register using an inner register function that can be used
@@ -326,7 +342,19 @@ impl<F> AsyncRegister<F> {
         'fut: 'cb,
         F: Fn(AsyncCbHandler<'cb, 'fut, T>) -> U,
     {
-        Self { register_fn }
+        Self { register_fn, policy: BufferPolicy::default() }
+    }
+
+    /// Sets the policy of the buffer of listeners created by this register,
+    /// i.e. what happens to occurences of the event that arrive before the
+    /// previous ones are consumed. By default, [`BufferPolicy::CoalesceLatest`]
+    /// is used.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the policy has a capacity of zero.
+    pub fn buffered(self, policy: BufferPolicy) -> Self {
+        Self { policy: policy.validate(), ..self }
     }
 
     /// Registers a callback and lets it listen for the target event. A listener
@@ -541,6 +569,34 @@ impl<T> Listener<T> {
         ListenNext::new(self)
     }
 
+    /// Changes the policy of the buffer of this listener. If the buffer holds
+    /// more occurences than the new policy allows, the excess is dropped
+    /// according to the new policy.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the policy has a capacity of zero.
+    pub fn set_buffer_policy(&self, policy: BufferPolicy) {
+        self.inner.set_buffer_policy(policy);
+    }
+
+    /// Sets the policy of the buffer of this listener, returning the listener
+    /// back. Useful right after the listener is created.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the policy has a capacity of zero.
+    pub fn buffered(self, policy: BufferPolicy) -> Self {
+        self.set_buffer_policy(policy);
+        self
+    }
+
+    /// Returns how many occurences of the event were dropped so far because of
+    /// the policy of the buffer.
+    pub fn dropped(&self) -> u64 {
+        self.inner.dropped()
+    }
+
     fn generic_poll(
         &self,
         ctx: &mut task::Context<'_>,
@@ -1,4 +1,4 @@
-use std::{cell::Cell, error::Error, fmt, rc::Rc, task};
+use std::{cell::Cell, collections::VecDeque, error::Error, fmt, rc::Rc, task};
 
 /// An error that might happen when the callback's future is cancelled.
 #[derive(Debug)]
@@ -12,15 +12,134 @@ impl fmt::Display for Cancelled {
 
 impl Error for Cancelled {}
 
+/// Policy of the buffer of a multi-call listener, i.e. what happens to
+/// occurences of an event that arrive while previous occurences were not
+/// consumed yet. Occurences discarded because of the policy are counted as
+/// dropped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum BufferPolicy {
+    /// Keeps only the latest occurence, replacing any occurence not consumed
+    /// yet. This is the default policy.
+    #[default]
+    CoalesceLatest,
+    /// Keeps every occurence, the buffer grows without bounds.
+    Unbounded,
+    /// Keeps at most the given number of occurences, which must be positive.
+    /// When the buffer is full, the oldest occurence is dropped to make room
+    /// for the new one.
+    DropOldest(usize),
+    /// Keeps at most the given number of occurences, which must be positive.
+    /// When the buffer is full, the new occurence is dropped.
+    DropNewest(usize),
+}
+
+impl BufferPolicy {
+    pub(crate) fn capacity(self) -> Option<usize> {
+        match self {
+            Self::CoalesceLatest => Some(1),
+            Self::Unbounded => None,
+            Self::DropOldest(capacity) | Self::DropNewest(capacity) => {
+                Some(capacity)
+            },
+        }
+    }
+
+    pub(crate) fn validate(self) -> Self {
+        assert!(
+            self.capacity() != Some(0),
+            "buffer policy capacity must be positive"
+        );
+        self
+    }
+}
+
+/// A buffer of occurences, bounded according to a [`BufferPolicy`].
+#[derive(Debug)]
+pub(crate) struct Buffer<T> {
+    policy: BufferPolicy,
+    queue: VecDeque<T>,
+    dropped: u64,
+}
+
+impl<T> Default for Buffer<T> {
+    fn default() -> Self {
+        Self::new(BufferPolicy::default())
+    }
+}
+
+impl<T> Buffer<T> {
+    pub(crate) fn new(policy: BufferPolicy) -> Self {
+        Self { policy: policy.validate(), queue: VecDeque::new(), dropped: 0 }
+    }
+
+    pub(crate) fn push(&mut self, data: T) {
+        let is_full = self
+            .policy
+            .capacity()
+            .is_some_and(|capacity| self.queue.len() >= capacity);
+        if is_full && matches!(self.policy, BufferPolicy::DropNewest(_)) {
+            self.dropped += 1;
+        } else {
+            self.queue.push_back(data);
+            self.shrink();
+        }
+    }
+
+    pub(crate) fn pop(&mut self) -> Option<T> {
+        self.queue.pop_front()
+    }
+
+    #[cfg(feature = "time")]
+    pub(crate) fn front(&self) -> Option<&T> {
+        self.queue.front()
+    }
+
+    #[cfg(feature = "time")]
+    pub(crate) fn policy(&self) -> BufferPolicy {
+        self.policy
+    }
+
+    pub(crate) fn set_policy(&mut self, policy: BufferPolicy) {
+        self.policy = policy.validate();
+        self.shrink();
+    }
+
+    pub(crate) fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    #[cfg(feature = "time")]
+    pub(crate) fn count_dropped(&mut self, count: u64) {
+        self.dropped += count;
+    }
+
+    fn shrink(&mut self) {
+        if let Some(capacity) = self.policy.capacity() {
+            while self.queue.len() > capacity {
+                if matches!(self.policy, BufferPolicy::DropNewest(_)) {
+                    self.queue.pop_back();
+                } else {
+                    self.queue.pop_front();
+                }
+                self.dropped += 1;
+            }
+        }
+    }
+}
+
 pub fn channel<T>() -> (Notifier<T>, Listener<T>) {
-    let channel = Channel::init_connected();
+    buffered_channel(BufferPolicy::default())
+}
+
+pub fn buffered_channel<T>(policy: BufferPolicy) -> (Notifier<T>, Listener<T>) {
+    let channel = Channel::init_connected(policy);
     (Notifier::new(channel.clone()), Listener::new(channel))
 }
 
 struct ChannelInner<T> {
     connected: Cell<bool>,
     waker: Cell<Option<task::Waker>>,
-    data: Cell<Option<T>>,
+    buffer: Cell<Buffer<T>>,
 }
 
 impl<T> fmt::Debug for ChannelInner<T>
@@ -29,27 +148,37 @@ where
 {
     fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
         let waker = self.waker.take();
-        let data = self.data.take();
+        let buffer = self.buffer.take();
         let result = fmtr
             .debug_struct("callback::Channel")
             .field("connected", &self.connected)
             .field("waker", &waker)
-            .field("data", &data)
+            .field("buffer", &buffer)
             .finish();
         self.waker.set(waker);
-        self.data.set(data);
+        self.buffer.set(buffer);
         result
     }
 }
 
 impl<T> ChannelInner<T> {
-    fn init_connected() -> Self {
+    fn init_connected(policy: BufferPolicy) -> Self {
         Self {
             connected: Cell::new(true),
             waker: Cell::new(None),
-            data: Cell::new(None),
+            buffer: Cell::new(Buffer::new(policy)),
         }
     }
+
+    fn with_buffer<F, A>(&self, visitor: F) -> A
+    where
+        F: FnOnce(&mut Buffer<T>) -> A,
+    {
+        let mut buffer = self.buffer.take();
+        let output = visitor(&mut buffer);
+        self.buffer.set(buffer);
+        output
+    }
 }
 
 #[derive(Debug)]
@@ -58,8 +187,8 @@ struct Channel<T> {
 }
 
 impl<T> Channel<T> {
-    fn init_connected() -> Self {
-        Self { inner: Rc::new(ChannelInner::init_connected()) }
+    fn init_connected(policy: BufferPolicy) -> Self {
+        Self { inner: Rc::new(ChannelInner::init_connected(policy)) }
     }
 
     fn is_connected(&self) -> bool {
@@ -88,7 +217,7 @@ impl<T> Notifier<T> {
     }
 
     pub fn send(&self, data: T) {
-        self.channel.inner.data.set(Some(data));
+        self.channel.inner.with_buffer(|buffer| buffer.push(data));
         self.notify();
     }
 
@@ -125,7 +254,7 @@ impl<T> Listener<T> {
     }
 
     pub fn receive(&self) -> Option<Result<T, Cancelled>> {
-        match self.channel.inner.data.take() {
+        match self.channel.inner.with_buffer(Buffer::pop) {
             Some(data) => Some(Ok(data)),
             None if self.channel.is_connected() => None,
             None => Some(Err(Cancelled)),
@@ -139,6 +268,17 @@ impl<T> Listener<T> {
         }
         self.channel.inner.waker.set(stored);
     }
+
+    pub fn set_buffer_policy(&self, policy: BufferPolicy) {
+        // Validated before the buffer is taken out of its cell, so that a
+        // rejected policy leaves the buffer in place.
+        let policy = policy.validate();
+        self.channel.inner.with_buffer(|buffer| buffer.set_policy(policy));
+    }
+
+    pub fn dropped(&self) -> u64 {
+        self.channel.inner.with_buffer(|buffer| buffer.dropped())
+    }
 }
 
 impl<T> Drop for Listener<T> {
@@ -42,6 +42,11 @@ use wasm_bindgen::{
 use wasm_bindgen_futures::future_to_promise;
 use web_sys::EventTarget;
 
+#[cfg(feature = "time")]
+use crate::time;
+#[cfg(feature = "time")]
+use std::{cell::RefCell, future, time::Duration};
+
 #[cfg(feature = "stream")]
 use futures::stream::Stream;
 
@@ -91,6 +96,106 @@ impl<T> Listener<T> {
     pub fn listen_next<'this>(&'this self) -> ListenNext<'this, T> {
         ListenNext { listener: self.inner.listen_next() }
     }
+
+    /// Changes the policy of the buffer of this listener, i.e. what happens to
+    /// events that occur before the previous ones are consumed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the policy has a capacity of zero.
+    pub fn set_buffer_policy(&self, policy: callback::BufferPolicy) {
+        self.inner.set_buffer_policy(policy);
+    }
+
+    /// Sets the policy of the buffer of this listener, returning the listener
+    /// back.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the policy has a capacity of zero.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use webio::{callback::BufferPolicy, event::{Click, EventType}};
+    ///
+    /// # fn main() {
+    /// # webio::task::detach(async {
+    /// let document =
+    ///     web_sys::window().expect("only browser supported").document().unwrap();
+    /// let element = document.create_element("button").unwrap();
+    ///
+    /// let listener =
+    ///     Click.add_listener(&element).buffered(BufferPolicy::Unbounded);
+    /// element.dispatch_event(&web_sys::MouseEvent::new("click").unwrap()).unwrap();
+    /// element.dispatch_event(&web_sys::MouseEvent::new("click").unwrap()).unwrap();
+    /// listener.listen_next().await.unwrap();
+    /// listener.listen_next().await.unwrap();
+    /// assert_eq!(listener.dropped(), 0);
+    /// # });
+    /// # }
+    /// ```
+    pub fn buffered(self, policy: callback::BufferPolicy) -> Self {
+        self.set_buffer_policy(policy);
+        self
+    }
+
+    /// Returns how many events were dropped so far because of the policy of
+    /// the buffer.
+    pub fn dropped(&self) -> u64 {
+        self.inner.dropped()
+    }
+
+    /// Debounces this listener: after an event occurs, waits until no other
+    /// event occurs for the given duration, and then yields only the data of
+    /// the last event.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use webio::event::{EventType, KeyUp};
+    ///
+    /// # fn main() {
+    /// # webio::task::detach(async {
+    /// let document =
+    ///     web_sys::window().expect("only browser supported").document().unwrap();
+    /// let input = document.create_element("input").unwrap();
+    ///
+    /// let listener = KeyUp.add_listener(&input).debounce(Duration::from_millis(300));
+    /// while let Ok(_event) = listener.listen_next().await {
+    ///     // Searches only once the user stops typing.
+    /// }
+    /// # });
+    /// # }
+    /// ```
+    #[cfg(feature = "time")]
+    #[cfg_attr(feature = "feature-doc-cfg", doc(cfg(feature = "time")))]
+    pub fn debounce(self, duration: Duration) -> Debounced<T> {
+        Debounced {
+            listener: self,
+            debouncer: RefCell::new(time::Debouncer::new(duration)),
+        }
+    }
+
+    /// Throttles this listener: yields the data of an event right away, and
+    /// then drops the events that occur during the given duration.
+    #[cfg(feature = "time")]
+    #[cfg_attr(feature = "feature-doc-cfg", doc(cfg(feature = "time")))]
+    pub fn throttle(self, duration: Duration) -> Throttled<T> {
+        Throttled {
+            listener: self,
+            throttler: RefCell::new(time::Throttler::new(duration)),
+        }
+    }
+
+    #[cfg(feature = "time")]
+    fn poll_next_data(
+        &self,
+        ctx: &mut task::Context<'_>,
+    ) -> task::Poll<Option<T>> {
+        Pin::new(&mut self.listen_next()).poll(ctx).map(Result::ok)
+    }
 }
 
 impl<T> Drop for Listener<T> {
@@ -113,6 +218,92 @@ impl<T> Stream for Listener<T> {
     }
 }
 
+/// A listener debounced by [`Listener::debounce`].
+#[cfg(feature = "time")]
+#[cfg_attr(feature = "feature-doc-cfg", doc(cfg(feature = "time")))]
+pub struct Debounced<T> {
+    listener: Listener<T>,
+    debouncer: RefCell<time::Debouncer<T>>,
+}
+
+#[cfg(feature = "time")]
+impl<T> Debounced<T> {
+    /// Waits for the data of the last event of the next burst of events.
+    pub async fn listen_next(&self) -> Result<T, callback::Cancelled> {
+        future::poll_fn(|ctx| self.poll_debounced(ctx)).await
+    }
+
+    /// Returns the inner listener, discarding a pending event, if any.
+    pub fn into_inner(self) -> Listener<T> {
+        self.listener
+    }
+
+    fn poll_debounced(
+        &self,
+        ctx: &mut task::Context<'_>,
+    ) -> task::Poll<Result<T, callback::Cancelled>> {
+        self.debouncer
+            .borrow_mut()
+            .poll(ctx, |ctx| self.listener.poll_next_data(ctx))
+            .map(|data| data.ok_or(callback::Cancelled))
+    }
+}
+
+#[cfg(all(feature = "time", feature = "stream"))]
+impl<T> Stream for Debounced<T> {
+    type Item = T;
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        ctx: &mut task::Context<'_>,
+    ) -> task::Poll<Option<Self::Item>> {
+        self.poll_debounced(ctx).map(Result::ok)
+    }
+}
+
+/// A listener throttled by [`Listener::throttle`].
+#[cfg(feature = "time")]
+#[cfg_attr(feature = "feature-doc-cfg", doc(cfg(feature = "time")))]
+pub struct Throttled<T> {
+    listener: Listener<T>,
+    throttler: RefCell<time::Throttler>,
+}
+
+#[cfg(feature = "time")]
+impl<T> Throttled<T> {
+    /// Waits for the data of the first event after the current window.
+    pub async fn listen_next(&self) -> Result<T, callback::Cancelled> {
+        future::poll_fn(|ctx| self.poll_throttled(ctx)).await
+    }
+
+    /// Returns the inner listener.
+    pub fn into_inner(self) -> Listener<T> {
+        self.listener
+    }
+
+    fn poll_throttled(
+        &self,
+        ctx: &mut task::Context<'_>,
+    ) -> task::Poll<Result<T, callback::Cancelled>> {
+        self.throttler
+            .borrow_mut()
+            .poll(ctx, |ctx| self.listener.poll_next_data(ctx))
+            .map(|data| data.ok_or(callback::Cancelled))
+    }
+}
+
+#[cfg(all(feature = "time", feature = "stream"))]
+impl<T> Stream for Throttled<T> {
+    type Item = T;
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        ctx: &mut task::Context<'_>,
+    ) -> task::Poll<Option<Self::Item>> {
+        self.poll_throttled(ctx).map(Result::ok)
+    }
+}
+
 /// A single interval tick that can be awaited.
 pub struct ListenNext<'listener, T> {
     listener: callback::multi::ListenNext<'listener, T>,
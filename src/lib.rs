@@ -29,23 +29,15 @@
 //! `2305843009213693951`.
 //!
 //! ```no_run
-//! use std::time::Duration;
 //! use num::{BigUint, Zero};
 //! use wasm_bindgen::JsValue;
 //! use webio::event::{self, EventType};
 //!
-//! /// Number of steps before yielding control back to browser when testing
-//! /// whether a number is prime or not, in order not to freeze the browser with
-//! /// computations on large numbers. Of course, yielding back to the browser is
-//! /// just a pause, so after a few milliseconds later, WASM can resume its job on
-//! /// the current number.
-//! ///
-//! /// However, note that this applies only when a number is being tested,
-//! /// otherwise WASM sleeps and won't wake up until the button is pressed.
-//! const YIELD_STEPS: u16 = 20000;
-//!
 //! /// Tests if the given number is prime, asynchronous because it will pause the
-//! /// execution after some steps.
+//! /// execution whenever it runs for longer than the frame budget, in order not
+//! /// to freeze the browser with computations on large numbers. Of course,
+//! /// yielding back to the browser is just a pause, so the browser can render and
+//! /// handle input, and then WASM resumes its job on the current number.
 //! async fn is_prime(number: &BigUint) -> bool {
 //!     let two = BigUint::from(2u8);
 //!     if *number < two {
@@ -64,9 +56,7 @@
 //!         if (number % &attempt).is_zero() {
 //!             return false;
 //!         }
-//!         if (&attempt / &two % YIELD_STEPS).is_zero() {
-//!             webio::time::timeout(Duration::from_millis(10)).await;
-//!         }
+//!         webio::task::consume_budget().await;
 //!         attempt += &two;
 //!         square = &attempt * &attempt;
 //!     }
@@ -176,3 +166,7 @@ pub mod time;
 #[cfg(feature = "event")]
 #[cfg_attr(feature = "feature-doc-cfg", doc(cfg(feature = "event")))]
 pub mod event;
+
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg_attr(feature = "feature-doc-cfg", doc(cfg(not(target_arch = "wasm32"))))]
+pub mod native;
@@ -15,7 +15,7 @@
 /// # }
 /// ```
 #[cfg(feature = "macros")]
-#[cfg_attr(feature = "feature-tags", doc(cfg(feature = "macros")))]
+#[cfg_attr(feature = "feature-doc-cfg", doc(cfg(feature = "macros")))]
 #[macro_export]
 macro_rules! console_log {
     ($($arguments:tt)*) => {
@@ -38,7 +38,7 @@ macro_rules! console_log {
 /// # }
 /// ```
 #[cfg(feature = "macros")]
-#[cfg_attr(feature = "feature-tags", doc(cfg(feature = "macros")))]
+#[cfg_attr(feature = "feature-doc-cfg", doc(cfg(feature = "macros")))]
 #[macro_export]
 macro_rules! console_debug {
     ($($arguments:tt)*) => {
@@ -63,7 +63,7 @@ macro_rules! console_debug {
 /// # }
 /// ```
 #[cfg(feature = "macros")]
-#[cfg_attr(feature = "feature-tags", doc(cfg(feature = "macros")))]
+#[cfg_attr(feature = "feature-doc-cfg", doc(cfg(feature = "macros")))]
 #[macro_export]
 macro_rules! console_info {
     ($($arguments:tt)*) => {
@@ -86,7 +86,7 @@ macro_rules! console_info {
 /// # }
 /// ```
 #[cfg(feature = "macros")]
-#[cfg_attr(feature = "feature-tags", doc(cfg(feature = "macros")))]
+#[cfg_attr(feature = "feature-doc-cfg", doc(cfg(feature = "macros")))]
 #[macro_export]
 macro_rules! console_warn {
     ($($arguments:tt)*) => {
@@ -111,7 +111,7 @@ macro_rules! console_warn {
 /// # }
 /// ```
 #[cfg(feature = "macros")]
-#[cfg_attr(feature = "feature-tags", doc(cfg(feature = "macros")))]
+#[cfg_attr(feature = "feature-doc-cfg", doc(cfg(feature = "macros")))]
 #[macro_export]
 macro_rules! console_error {
     ($($arguments:tt)*) => {
@@ -143,10 +143,71 @@ macro_rules! console_error {
 /// }
 /// ```
 #[cfg(feature = "macros")]
-#[cfg_attr(feature = "feature-tags", doc(cfg(feature = "macros")))]
+#[cfg_attr(feature = "feature-doc-cfg", doc(cfg(feature = "macros")))]
 #[macro_export]
 macro_rules! run_tests_in_browser {
     {} => {
         $crate::wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
     };
 }
+
+/// Declares task-local keys, i.e. static [`LocalKey`](crate::task::LocalKey)s
+/// whose values are set per task through
+/// [`LocalKey::scope`](crate::task::LocalKey::scope).
+///
+/// Syntax:
+/// ```ignore
+/// task_local! {
+///     [pub] static NAME: Type;
+///     ...
+/// }
+/// ```
+///
+/// # Examples
+/// ```no_run
+/// use webio::task;
+///
+/// webio::task_local! {
+///     pub static SESSION: String;
+///     static REQUEST_ID: u32;
+/// }
+///
+/// # fn main() {
+/// # task::detach(async {
+/// let first = task::spawn(REQUEST_ID.scope(1, async {
+///     task::yield_now().await;
+///     REQUEST_ID.get()
+/// }));
+/// let second = task::spawn(REQUEST_ID.scope(2, async {
+///     task::yield_now().await;
+///     REQUEST_ID.get()
+/// }));
+/// assert_eq!(first.await.unwrap(), 1);
+/// assert_eq!(second.await.unwrap(), 2);
+/// # });
+/// # }
+/// ```
+#[macro_export]
+macro_rules! task_local {
+    () => {};
+
+    (
+        $(#[$attr:meta])*
+        $vis:vis static $name:ident: $type:ty;
+        $($rest:tt)*
+    ) => {
+        $crate::task_local!($(#[$attr])* $vis static $name: $type);
+        $crate::task_local!($($rest)*);
+    };
+
+    ($(#[$attr:meta])* $vis:vis static $name:ident: $type:ty) => {
+        $(#[$attr])*
+        $vis static $name: $crate::task::LocalKey<$type> = {
+            ::std::thread_local! {
+                static INNER: ::std::cell::RefCell<::std::option::Option<$type>> =
+                    const { ::std::cell::RefCell::new(::std::option::Option::None) };
+            }
+            $crate::task::LocalKey { inner: INNER }
+        };
+    };
+}
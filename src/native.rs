@@ -0,0 +1,101 @@
+//! This module implements the native backend, used when not targeting WASM, so
+//! that code built on webio can be run and tested with plain `cargo test`.
+//!
+//! On native targets, tasks run on a small single-threaded executor, and timers
+//! are driven by a clock that is either the system's monotonic clock or a
+//! virtual one. The executor only makes progress inside [`block_on`] (or
+//! [`Runtime::block_on`]), which [`webio::test`](crate::test) uses under the
+//! hood on native targets.
+
+mod executor;
+mod timers;
+
+use std::future::Future;
+
+pub(crate) use executor::spawn_local;
+#[cfg(feature = "time")]
+pub(crate) use timers::{clear_timer, now, set_timer, TimerId};
+
+/// The clock driving timers and [`Instant`](crate::time::Instant) on native
+/// targets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Clock {
+    /// The system's monotonic clock. Waiting for a timer actually sleeps.
+    #[default]
+    Real,
+    /// A virtual clock, which only moves forward when every task is waiting
+    /// for a timer, jumping straight to the next timer's deadline. Waiting for
+    /// a timer never sleeps.
+    Virtual,
+}
+
+/// A configurable runtime for the native backend.
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+/// use webio::{
+///     native::{Clock, Runtime},
+///     time::{timeout, Instant},
+/// };
+///
+/// let elapsed = Runtime::new().clock(Clock::Virtual).block_on(async {
+///     let then = Instant::now();
+///     timeout(Duration::from_secs(3600)).await;
+///     then.elapsed()
+/// });
+/// assert_eq!(elapsed, Duration::from_secs(3600));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Runtime {
+    clock: Clock,
+}
+
+impl Runtime {
+    /// Creates a runtime with the default configuration, i.e. driven by the
+    /// real clock.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the clock driving timers.
+    pub fn clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Runs the given future to completion in the current thread, running
+    /// spawned tasks and firing timers while the future is not complete.
+    ///
+    /// Tasks still alive when the future completes are kept, and they resume
+    /// the next time a future is blocked on in the same thread.
+    ///
+    /// # Panics
+    /// Panics if called from within another `block_on` call.
+    pub fn block_on<A>(self, future: A) -> A::Output
+    where
+        A: Future,
+    {
+        timers::set_clock(self.clock);
+        executor::block_on(future)
+    }
+}
+
+/// Runs the given future to completion in the current thread using the real
+/// clock. Shorthand for `Runtime::new().block_on(future)`.
+///
+/// # Examples
+/// ```
+/// use webio::{native, task};
+///
+/// let output = native::block_on(async {
+///     task::spawn(async { 2 + 2 }).await.unwrap()
+/// });
+/// assert_eq!(output, 4);
+/// ```
+pub fn block_on<A>(future: A) -> A::Output
+where
+    A: Future,
+{
+    Runtime::new().block_on(future)
+}
@@ -0,0 +1,196 @@
+//! Implementation of the single-threaded executor of the native backend.
+
+use super::timers;
+use std::{
+    cell::{Cell, RefCell},
+    collections::{HashMap, VecDeque},
+    future::Future,
+    pin::{pin, Pin},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+        Mutex,
+    },
+    task,
+    thread::{self, Thread},
+};
+
+type TaskId = u64;
+
+/// Identifier reserved for the future being blocked on.
+const MAIN_TASK: TaskId = TaskId::MAX;
+
+/// Part of the executor shared with wakers, which might be woken from another
+/// thread.
+struct Shared {
+    ready: Mutex<VecDeque<TaskId>>,
+    thread: Thread,
+}
+
+impl Shared {
+    fn push(&self, id: TaskId) {
+        self.ready.lock().unwrap().push_back(id);
+        self.thread.unpark();
+    }
+
+    fn pop(&self) -> Option<TaskId> {
+        self.ready.lock().unwrap().pop_front()
+    }
+}
+
+struct TaskWaker {
+    id: TaskId,
+    queued: AtomicBool,
+    shared: Arc<Shared>,
+}
+
+impl TaskWaker {
+    fn new(id: TaskId, shared: Arc<Shared>) -> Arc<Self> {
+        Arc::new(Self { id, queued: AtomicBool::new(false), shared })
+    }
+
+    fn schedule(&self) {
+        if !self.queued.swap(true, Ordering::AcqRel) {
+            self.shared.push(self.id);
+        }
+    }
+}
+
+impl task::Wake for TaskWaker {
+    fn wake(self: Arc<Self>) {
+        self.schedule();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.schedule();
+    }
+}
+
+struct Task {
+    future: Pin<Box<dyn Future<Output = ()>>>,
+    waker: Arc<TaskWaker>,
+}
+
+struct Executor {
+    shared: Arc<Shared>,
+    next_id: Cell<TaskId>,
+    tasks: RefCell<HashMap<TaskId, Task>>,
+    running: Cell<bool>,
+}
+
+impl Executor {
+    fn new() -> Self {
+        let shared = Arc::new(Shared {
+            ready: Mutex::new(VecDeque::new()),
+            thread: thread::current(),
+        });
+        Self {
+            shared,
+            next_id: Cell::new(0),
+            tasks: RefCell::new(HashMap::new()),
+            running: Cell::new(false),
+        }
+    }
+}
+
+impl Drop for Executor {
+    fn drop(&mut self) {
+        // Tasks still alive when the thread exits are leaked, as dropping them
+        // could access thread-local storage that is already destroyed.
+        for (_, task) in self.tasks.get_mut().drain() {
+            std::mem::forget(task);
+        }
+    }
+}
+
+thread_local! {
+    static EXECUTOR: Executor = Executor::new();
+}
+
+/// Spawns a task in the executor of the current thread. It only runs while a
+/// future is blocked on in this thread.
+pub(crate) fn spawn_local<A>(future: A)
+where
+    A: Future<Output = ()> + 'static,
+{
+    EXECUTOR.with(|executor| {
+        let id = executor.next_id.get();
+        executor.next_id.set(id + 1);
+        let waker = TaskWaker::new(id, executor.shared.clone());
+        waker.schedule();
+        let task = Task { future: Box::pin(future), waker };
+        executor.tasks.borrow_mut().insert(id, task);
+    })
+}
+
+/// Polls the given task once, if it still exists.
+fn run(id: TaskId) {
+    let Some(mut task) =
+        EXECUTOR.with(|executor| executor.tasks.borrow_mut().remove(&id))
+    else {
+        return;
+    };
+    task.waker.queued.store(false, Ordering::Release);
+    let waker = task::Waker::from(task.waker.clone());
+    let mut ctx = task::Context::from_waker(&waker);
+    if task.future.as_mut().poll(&mut ctx).is_pending() {
+        EXECUTOR.with(|executor| executor.tasks.borrow_mut().insert(id, task));
+    }
+}
+
+struct RunningGuard;
+
+impl Drop for RunningGuard {
+    fn drop(&mut self) {
+        EXECUTOR.with(|executor| executor.running.set(false));
+    }
+}
+
+pub(super) fn block_on<A>(future: A) -> A::Output
+where
+    A: Future,
+{
+    let shared = EXECUTOR.with(|executor| {
+        if executor.running.replace(true) {
+            panic!("Cannot block on a future while already blocking on one")
+        }
+        executor.shared.clone()
+    });
+    let _guard = RunningGuard;
+
+    let mut future = pin!(future);
+    let main_waker = TaskWaker::new(MAIN_TASK, shared.clone());
+    main_waker.schedule();
+    let waker = task::Waker::from(main_waker.clone());
+    let mut ctx = task::Context::from_waker(&waker);
+
+    loop {
+        // Like microtasks, every ready task runs before the next timer fires.
+        while let Some(id) = shared.pop() {
+            if id == MAIN_TASK {
+                main_waker.queued.store(false, Ordering::Release);
+                if let task::Poll::Ready(output) =
+                    future.as_mut().poll(&mut ctx)
+                {
+                    return output;
+                }
+            } else {
+                run(id);
+            }
+        }
+
+        if timers::fire_next() {
+            continue;
+        }
+
+        match timers::next_deadline() {
+            Some(deadline) if timers::is_virtual() => {
+                timers::advance_to(deadline)
+            },
+            Some(deadline) => {
+                thread::park_timeout(deadline.saturating_sub(timers::now()))
+            },
+            None => thread::park(),
+        }
+    }
+}
@@ -0,0 +1,145 @@
+//! Implementation of the clock and of the timers of the native backend.
+
+// Timers are only set by the `time` module.
+#![cfg_attr(not(feature = "time"), allow(dead_code))]
+
+use super::Clock;
+use std::{
+    cell::{Cell, RefCell},
+    collections::{BTreeMap, HashMap},
+    time::{Duration, Instant},
+};
+
+/// Identifier of a timer set through [`set_timer`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub(crate) struct TimerId(u64);
+
+type Callback = Box<dyn FnOnce()>;
+
+struct Timers {
+    clock: Cell<Clock>,
+    /// The instant at which the real clock was last switched to, and the time
+    /// it then read, so that it continues from where the previous clock was.
+    real_origin: Cell<(Instant, Duration)>,
+    virtual_now: Cell<Duration>,
+    next_id: Cell<u64>,
+    queue: RefCell<BTreeMap<(Duration, TimerId), Callback>>,
+    deadlines: RefCell<HashMap<TimerId, Duration>>,
+}
+
+impl Timers {
+    fn new() -> Self {
+        Self {
+            clock: Cell::new(Clock::Real),
+            real_origin: Cell::new((Instant::now(), Duration::ZERO)),
+            virtual_now: Cell::new(Duration::ZERO),
+            next_id: Cell::new(0),
+            queue: RefCell::new(BTreeMap::new()),
+            deadlines: RefCell::new(HashMap::new()),
+        }
+    }
+
+    fn now(&self) -> Duration {
+        match self.clock.get() {
+            Clock::Real => {
+                let (origin, offset) = self.real_origin.get();
+                offset + origin.elapsed()
+            },
+            Clock::Virtual => self.virtual_now.get(),
+        }
+    }
+}
+
+impl Drop for Timers {
+    fn drop(&mut self) {
+        // Pending callbacks are leaked when the thread exits, for the same
+        // reason as tasks still alive.
+        let queue = std::mem::take(self.queue.get_mut());
+        std::mem::forget(queue);
+    }
+}
+
+thread_local! {
+    static TIMERS: Timers = Timers::new();
+}
+
+pub(super) fn set_clock(clock: Clock) {
+    TIMERS.with(|timers| {
+        // Both clocks continue from the current time, so that time never goes
+        // backwards when switching clocks.
+        let now = timers.now();
+        timers.virtual_now.set(now);
+        timers.real_origin.set((Instant::now(), now));
+        timers.clock.set(clock);
+    })
+}
+
+/// Time elapsed since the clock's origin, according to the clock in use.
+pub(crate) fn now() -> Duration {
+    TIMERS.with(Timers::now)
+}
+
+/// Sets a timer calling the given callback once the given delay has passed,
+/// unless the timer is cleared before.
+pub(crate) fn set_timer<F>(delay: Duration, callback: F) -> TimerId
+where
+    F: FnOnce() + 'static,
+{
+    TIMERS.with(|timers| {
+        let id = TimerId(timers.next_id.get());
+        timers.next_id.set(id.0 + 1);
+        let deadline = timers.now() + delay;
+        timers.queue.borrow_mut().insert((deadline, id), Box::new(callback));
+        timers.deadlines.borrow_mut().insert(id, deadline);
+        id
+    })
+}
+
+/// Clears the given timer, so that its callback is no longer called. Clearing
+/// an expired or already cleared timer has no effect.
+pub(crate) fn clear_timer(id: TimerId) {
+    TIMERS.with(|timers| {
+        if let Some(deadline) = timers.deadlines.borrow_mut().remove(&id) {
+            timers.queue.borrow_mut().remove(&(deadline, id));
+        }
+    })
+}
+
+/// Deadline of the next timer to expire, if any.
+pub(super) fn next_deadline() -> Option<Duration> {
+    TIMERS.with(|timers| {
+        timers.queue.borrow().keys().next().map(|(deadline, _)| *deadline)
+    })
+}
+
+/// Moves the clock to the given deadline, if it is virtual and the deadline is
+/// in its future.
+pub(super) fn advance_to(deadline: Duration) {
+    TIMERS.with(|timers| {
+        if timers.clock.get() == Clock::Virtual
+            && timers.virtual_now.get() < deadline
+        {
+            timers.virtual_now.set(deadline);
+        }
+    })
+}
+
+/// Whether the clock is virtual.
+pub(super) fn is_virtual() -> bool {
+    TIMERS.with(|timers| timers.clock.get() == Clock::Virtual)
+}
+
+/// Fires the earliest expired timer, if any, returning whether a timer fired.
+pub(super) fn fire_next() -> bool {
+    let expired = TIMERS.with(|timers| {
+        let now = timers.now();
+        let mut queue = timers.queue.borrow_mut();
+        let key =
+            *queue.keys().next().filter(|(deadline, _)| *deadline <= now)?;
+        timers.deadlines.borrow_mut().remove(&key.1);
+        queue.remove(&key)
+    });
+    let Some(callback) = expired else { return false };
+    callback();
+    true
+}
@@ -3,11 +3,36 @@
 //! Well, when using async Rust, a critical operation can be split by an
 //! `.await` expression. In fact, these locks are designed specially for that:
 //! ensuring a critical operation is performed as if it were atomic even if you
-//! insert an `.await` between two steps.
+//! insert an `.await` between two steps. A [`Semaphore`] is also provided, to
+//! limit how many of such operations run concurrently, as well as [`Notify`],
+//! to signal between tasks, and the [`channel`] module provides channels to
+//! send values between tasks.
+
+pub mod channel;
 
 mod mutex;
+mod notify;
 mod rw_lock;
+mod semaphore;
+
+pub use mutex::{MappedMutexGuard, Mutex, MutexGuard, OwnedMutexGuard};
+
+pub use notify::{Notified, Notify};
 
-pub use mutex::{Mutex, MutexGuard};
+pub use rw_lock::{
+    MappedReadGuard,
+    MappedWriteGuard,
+    OwnedReadGuard,
+    OwnedWriteGuard,
+    ReadGuard,
+    RwLock,
+    UpgradeableReadGuard,
+    WriteGuard,
+};
 
-pub use rw_lock::{ReadGuard, RwLock, WriteGuard};
+pub use semaphore::{
+    AcquireError,
+    Semaphore,
+    SemaphorePermit,
+    TryAcquireError,
+};
@@ -0,0 +1,25 @@
+//! This module provides channels to send values between tasks of a single
+//! instance of a Rust WebAssembly module. Channels are built upon [`Rc`], and
+//! so their handles are neither [`Send`] nor [`Sync`]: they are meant to be
+//! moved into tasks spawned with [`task::spawn`](crate::task::spawn), not into
+//! other threads.
+//!
+//! - [`oneshot`]: a single value sent from a single producer to a single
+//!   consumer.
+//! - [`mpsc`]: many values sent from many producers to a single consumer,
+//!   either through a bounded or an unbounded buffer.
+//! - [`broadcast`]: many values sent from many producers to many consumers,
+//!   each consumer receiving every value, and detecting values it missed
+//!   because it lagged behind.
+//! - [`watch`]: a single value updated by a single producer and observed by
+//!   many consumers, which are notified of changes.
+//!
+//! Under the `stream` feature, receivers of multiple values can also be used
+//! as streams.
+//!
+//! [`Rc`]: std::rc::Rc
+
+pub mod broadcast;
+pub mod mpsc;
+pub mod oneshot;
+pub mod watch;
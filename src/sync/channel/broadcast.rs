@@ -0,0 +1,348 @@
+//! A channel to send many values from many producers to many consumers, where
+//! every consumer receives every value. Values are kept in a buffer of fixed
+//! capacity: when it is full, the oldest value is dropped, and consumers that
+//! had not received it yet are told how many values they missed through
+//! [`RecvError::Lagged`].
+//!
+//! ```no_run
+//! use webio::{sync::channel::broadcast, task};
+//!
+//! # fn main() {
+//! # task::detach(async {
+//! let (sender, mut receiver0) = broadcast::channel(16);
+//! let mut receiver1 = sender.subscribe();
+//!
+//! let handle = task::spawn(async move {
+//!     assert_eq!(receiver1.recv().await, Ok("hello"));
+//! });
+//! sender.send("hello").unwrap();
+//! assert_eq!(receiver0.recv().await, Ok("hello"));
+//! handle.await.unwrap();
+//! # });
+//! # }
+//! ```
+
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, VecDeque},
+    error::Error,
+    fmt,
+    future,
+    rc::Rc,
+    task::{Context, Poll, Waker},
+};
+
+#[cfg(feature = "stream")]
+use futures::stream::Stream;
+#[cfg(feature = "stream")]
+use std::pin::Pin;
+
+type Token = usize;
+
+/// The error returned by [`Sender::send`] when there are no receivers. The
+/// value is given back.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct SendError<T>(pub T);
+
+impl<T> fmt::Debug for SendError<T> {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        fmtr.pad("SendError { .. }")
+    }
+}
+
+impl<T> fmt::Display for SendError<T> {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmtr, "channel closed")
+    }
+}
+
+impl<T> Error for SendError<T> {}
+
+/// The error returned by [`Receiver::recv`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecvError {
+    /// All senders were dropped, and every value was received.
+    Closed,
+    /// The receiver lagged behind, and the given number of values were dropped
+    /// from the buffer before it could receive them. The next call receives
+    /// the oldest value still buffered.
+    Lagged(u64),
+}
+
+impl fmt::Display for RecvError {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Closed => write!(fmtr, "channel closed"),
+            Self::Lagged(missed) => {
+                write!(fmtr, "channel lagged by {}", missed)
+            },
+        }
+    }
+}
+
+impl Error for RecvError {}
+
+/// The error returned by [`Receiver::try_recv`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TryRecvError {
+    /// No value was sent since the last one received, but senders are still
+    /// alive.
+    Empty,
+    /// All senders were dropped, and every value was received.
+    Closed,
+    /// The receiver lagged behind, and the given number of values were dropped
+    /// from the buffer before it could receive them. The next call receives
+    /// the oldest value still buffered.
+    Lagged(u64),
+}
+
+impl fmt::Display for TryRecvError {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Empty => write!(fmtr, "channel empty"),
+            Self::Closed => write!(fmtr, "channel closed"),
+            Self::Lagged(missed) => {
+                write!(fmtr, "channel lagged by {}", missed)
+            },
+        }
+    }
+}
+
+impl Error for TryRecvError {}
+
+#[derive(Debug)]
+struct State<T> {
+    buffer: VecDeque<T>,
+    capacity: usize,
+    head: u64,
+    senders: usize,
+    receivers: BTreeMap<Token, Option<Waker>>,
+}
+
+impl<T> State<T> {
+    fn new_token(&self) -> Token {
+        self.receivers.last_key_value().map_or(0, |(token, _)| token + 1)
+    }
+
+    fn tail(&self) -> u64 {
+        self.head + self.buffer.len() as u64
+    }
+
+    fn subscribe(&mut self) -> Token {
+        let token = self.new_token();
+        self.receivers.insert(token, None);
+        token
+    }
+
+    fn send(&mut self, value: T) -> Result<usize, SendError<T>> {
+        if self.receivers.is_empty() {
+            return Err(SendError(value));
+        }
+        if self.buffer.len() == self.capacity {
+            self.buffer.pop_front();
+            self.head += 1;
+        }
+        self.buffer.push_back(value);
+        for waker in self.receivers.values_mut() {
+            if let Some(waker) = waker.take() {
+                waker.wake();
+            }
+        }
+        Ok(self.receivers.len())
+    }
+
+    fn drop_sender(&mut self) {
+        self.senders -= 1;
+        if self.senders == 0 {
+            for waker in self.receivers.values_mut() {
+                if let Some(waker) = waker.take() {
+                    waker.wake();
+                }
+            }
+        }
+    }
+}
+
+impl<T> State<T>
+where
+    T: Clone,
+{
+    fn try_recv(&self, next: &mut u64) -> Result<T, TryRecvError> {
+        if *next < self.head {
+            let missed = self.head - *next;
+            *next = self.head;
+            return Err(TryRecvError::Lagged(missed));
+        }
+        match self.buffer.get((*next - self.head) as usize) {
+            Some(value) => {
+                *next += 1;
+                Ok(value.clone())
+            },
+            None if self.senders == 0 => Err(TryRecvError::Closed),
+            None => Err(TryRecvError::Empty),
+        }
+    }
+}
+
+/// Creates a broadcast channel, returning a sender and a first receiver. At
+/// most `capacity` values are buffered. More receivers are created through
+/// [`Sender::subscribe`].
+///
+/// # Panics
+///
+/// Panics if `capacity` is zero.
+pub fn channel<T>(capacity: usize) -> (Sender<T>, Receiver<T>) {
+    assert!(capacity > 0, "broadcast channel capacity must be positive");
+    let mut state = State {
+        buffer: VecDeque::with_capacity(capacity),
+        capacity,
+        head: 0,
+        senders: 1,
+        receivers: BTreeMap::new(),
+    };
+    let token = state.subscribe();
+    let state = Rc::new(RefCell::new(state));
+    (Sender { state: state.clone() }, Receiver { state, token, next: 0 })
+}
+
+/// The sending half of a [`broadcast`](self) channel. Can be cloned to send
+/// from many tasks.
+pub struct Sender<T> {
+    state: Rc<RefCell<State<T>>>,
+}
+
+impl<T> Sender<T> {
+    /// Sends a value to every receiver alive, returning how many there are.
+    /// If there are none, the value is given back as an error.
+    pub fn send(&self, value: T) -> Result<usize, SendError<T>> {
+        self.state.borrow_mut().send(value)
+    }
+
+    /// Creates a new receiver, which receives the values sent from now on.
+    pub fn subscribe(&self) -> Receiver<T> {
+        let mut state = self.state.borrow_mut();
+        let token = state.subscribe();
+        let next = state.tail();
+        Receiver { state: self.state.clone(), token, next }
+    }
+
+    /// Returns how many receivers are alive.
+    pub fn receiver_count(&self) -> usize {
+        self.state.borrow().receivers.len()
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        self.state.borrow_mut().senders += 1;
+        Self { state: self.state.clone() }
+    }
+}
+
+impl<T> fmt::Debug for Sender<T> {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        fmtr.pad("Sender { .. }")
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        self.state.borrow_mut().drop_sender();
+    }
+}
+
+/// The receiving half of a [`broadcast`](self) channel.
+pub struct Receiver<T> {
+    state: Rc<RefCell<State<T>>>,
+    token: Token,
+    next: u64,
+}
+
+impl<T> Receiver<T> {
+    /// Creates a new receiver of the same channel, which receives the values
+    /// sent from now on, regardless of what this receiver did not receive
+    /// yet.
+    pub fn resubscribe(&self) -> Self {
+        let mut state = self.state.borrow_mut();
+        let token = state.subscribe();
+        let next = state.tail();
+        Self { state: self.state.clone(), token, next }
+    }
+
+    /// Returns how many values were sent but not received yet by this
+    /// receiver, including the ones it lagged behind.
+    pub fn len(&self) -> usize {
+        (self.state.borrow().tail() - self.next) as usize
+    }
+
+    /// Returns whether all values sent were received by this receiver.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T> Receiver<T>
+where
+    T: Clone,
+{
+    /// Receives the next value, waiting if this receiver already received
+    /// every value sent.
+    pub async fn recv(&mut self) -> Result<T, RecvError> {
+        future::poll_fn(|ctx| self.poll_recv(ctx)).await
+    }
+
+    /// Tries to receive the next value without blocking.
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        self.state.borrow().try_recv(&mut self.next)
+    }
+
+    fn poll_recv(
+        &mut self,
+        ctx: &mut Context<'_>,
+    ) -> Poll<Result<T, RecvError>> {
+        let mut state = self.state.borrow_mut();
+        match state.try_recv(&mut self.next) {
+            Ok(value) => Poll::Ready(Ok(value)),
+            Err(TryRecvError::Closed) => Poll::Ready(Err(RecvError::Closed)),
+            Err(TryRecvError::Lagged(missed)) => {
+                Poll::Ready(Err(RecvError::Lagged(missed)))
+            },
+            Err(TryRecvError::Empty) => {
+                state.receivers.insert(self.token, Some(ctx.waker().clone()));
+                Poll::Pending
+            },
+        }
+    }
+}
+
+impl<T> fmt::Debug for Receiver<T> {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        fmtr.pad("Receiver { .. }")
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.state.borrow_mut().receivers.remove(&self.token);
+    }
+}
+
+/// Yields [`RecvError::Lagged`] errors when values were missed, and ends when
+/// the channel is closed.
+#[cfg(feature = "stream")]
+impl<T> Stream for Receiver<T>
+where
+    T: Clone,
+{
+    type Item = Result<T, RecvError>;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        ctx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        self.poll_recv(ctx).map(|result| match result {
+            Err(RecvError::Closed) => None,
+            result => Some(result),
+        })
+    }
+}
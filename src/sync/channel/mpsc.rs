@@ -0,0 +1,496 @@
+//! A channel to send many values from many producers to a single consumer,
+//! either bounded, where producers wait while the buffer is full, or
+//! unbounded.
+//!
+//! ```no_run
+//! use webio::{sync::channel::mpsc, task};
+//!
+//! # fn main() {
+//! # task::detach(async {
+//! let (sender, mut receiver) = mpsc::channel(8);
+//! for i in 0 .. 3 {
+//!     let sender = sender.clone();
+//!     task::spawn(async move {
+//!         sender.send(i).await.unwrap();
+//!     });
+//! }
+//! drop(sender);
+//!
+//! let mut total = 0;
+//! while let Some(i) = receiver.recv().await {
+//!     total += i;
+//! }
+//! assert_eq!(total, 3);
+//! # });
+//! # }
+//! ```
+
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, VecDeque},
+    error::Error,
+    fmt,
+    future,
+    rc::Rc,
+    task::{Context, Poll, Waker},
+};
+
+#[cfg(feature = "stream")]
+use futures::stream::Stream;
+#[cfg(feature = "stream")]
+use std::pin::Pin;
+
+type Token = usize;
+
+/// The error returned when sending through a closed channel, i.e. when the
+/// receiver was dropped or closed. The value is given back.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct SendError<T>(pub T);
+
+impl<T> fmt::Debug for SendError<T> {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        fmtr.pad("SendError { .. }")
+    }
+}
+
+impl<T> fmt::Display for SendError<T> {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmtr, "channel closed")
+    }
+}
+
+impl<T> Error for SendError<T> {}
+
+/// The error returned by [`Sender::try_send`]. The value is given back.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum TrySendError<T> {
+    /// The buffer of the channel is full, or other tasks are already waiting
+    /// to send.
+    Full(T),
+    /// The receiver was dropped or closed.
+    Closed(T),
+}
+
+impl<T> TrySendError<T> {
+    /// Takes back the value that failed to be sent.
+    pub fn into_inner(self) -> T {
+        match self {
+            Self::Full(value) | Self::Closed(value) => value,
+        }
+    }
+}
+
+impl<T> fmt::Debug for TrySendError<T> {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Full(_) => fmtr.pad("Full(..)"),
+            Self::Closed(_) => fmtr.pad("Closed(..)"),
+        }
+    }
+}
+
+impl<T> fmt::Display for TrySendError<T> {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Full(_) => write!(fmtr, "channel full"),
+            Self::Closed(_) => write!(fmtr, "channel closed"),
+        }
+    }
+}
+
+impl<T> Error for TrySendError<T> {}
+
+/// The error returned by [`Receiver::try_recv`] and
+/// [`UnboundedReceiver::try_recv`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TryRecvError {
+    /// No value is buffered, but senders are still alive.
+    Empty,
+    /// No value is buffered, and all senders were dropped, or the receiver was
+    /// closed.
+    Disconnected,
+}
+
+impl fmt::Display for TryRecvError {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Empty => write!(fmtr, "channel empty"),
+            Self::Disconnected => write!(fmtr, "channel disconnected"),
+        }
+    }
+}
+
+impl Error for TryRecvError {}
+
+#[derive(Debug)]
+struct State<T> {
+    buffer: VecDeque<T>,
+    capacity: Option<usize>,
+    senders: usize,
+    receiver_alive: bool,
+    receiver_waker: Option<Waker>,
+    sends_on_hold: BTreeMap<Token, Waker>,
+}
+
+impl<T> State<T> {
+    fn new(capacity: Option<usize>) -> Self {
+        Self {
+            buffer: VecDeque::new(),
+            capacity,
+            senders: 1,
+            receiver_alive: true,
+            receiver_waker: None,
+            sends_on_hold: BTreeMap::new(),
+        }
+    }
+
+    fn new_token(&self) -> Token {
+        self.sends_on_hold.last_key_value().map_or(0, |(token, _)| token + 1)
+    }
+
+    fn has_room(&self) -> bool {
+        self.capacity.is_none_or(|capacity| self.buffer.len() < capacity)
+    }
+
+    fn push(&mut self, value: T) {
+        self.buffer.push_back(value);
+        if let Some(waker) = self.receiver_waker.take() {
+            waker.wake();
+        }
+    }
+
+    fn forward_sends(&mut self) {
+        if self.has_room() {
+            if let Some((_, waker)) = self.sends_on_hold.first_key_value() {
+                waker.wake_by_ref();
+            }
+        }
+    }
+
+    fn try_send(&mut self, value: T) -> Result<(), TrySendError<T>> {
+        if !self.receiver_alive {
+            Err(TrySendError::Closed(value))
+        } else if self.sends_on_hold.is_empty() && self.has_room() {
+            self.push(value);
+            Ok(())
+        } else {
+            Err(TrySendError::Full(value))
+        }
+    }
+
+    fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        match self.buffer.pop_front() {
+            Some(value) => {
+                self.forward_sends();
+                Ok(value)
+            },
+            None if self.senders == 0 || !self.receiver_alive => {
+                Err(TryRecvError::Disconnected)
+            },
+            None => Err(TryRecvError::Empty),
+        }
+    }
+
+    fn poll_recv(&mut self, ctx: &mut Context<'_>) -> Poll<Option<T>> {
+        match self.try_recv() {
+            Ok(value) => Poll::Ready(Some(value)),
+            Err(TryRecvError::Disconnected) => Poll::Ready(None),
+            Err(TryRecvError::Empty) => {
+                self.receiver_waker = Some(ctx.waker().clone());
+                Poll::Pending
+            },
+        }
+    }
+
+    fn close(&mut self) {
+        self.receiver_alive = false;
+        while let Some((_, waker)) = self.sends_on_hold.pop_first() {
+            waker.wake();
+        }
+    }
+
+    fn drop_sender(&mut self) {
+        self.senders -= 1;
+        if self.senders == 0 {
+            if let Some(waker) = self.receiver_waker.take() {
+                waker.wake();
+            }
+        }
+    }
+}
+
+/// Creates a bounded channel, returning the sending and the receiving halves.
+/// At most `capacity` values are buffered, and senders wait for room when the
+/// buffer is full, in the order they started waiting.
+///
+/// # Panics
+///
+/// Panics if `capacity` is zero.
+pub fn channel<T>(capacity: usize) -> (Sender<T>, Receiver<T>) {
+    assert!(capacity > 0, "mpsc channel capacity must be positive");
+    let state = Rc::new(RefCell::new(State::new(Some(capacity))));
+    (Sender { state: state.clone() }, Receiver { state })
+}
+
+/// Creates an unbounded channel, returning the sending and the receiving
+/// halves. Sending never waits, and the buffer grows without bounds.
+pub fn unbounded_channel<T>() -> (UnboundedSender<T>, UnboundedReceiver<T>) {
+    let state = Rc::new(RefCell::new(State::new(None)));
+    (UnboundedSender { state: state.clone() }, UnboundedReceiver { state })
+}
+
+/// The sending half of a bounded [`mpsc`](self) channel. Can be cloned to
+/// send from many tasks.
+pub struct Sender<T> {
+    state: Rc<RefCell<State<T>>>,
+}
+
+impl<T> Sender<T> {
+    /// Sends a value, waiting for room in the buffer if it is full. If the
+    /// receiver was dropped or closed, the value is given back as an error.
+    pub async fn send(&self, value: T) -> Result<(), SendError<T>> {
+        let mut subscriber = SendSubscriber {
+            state: &self.state,
+            value: Some(value),
+            token: None,
+        };
+        future::poll_fn(|ctx| subscriber.poll_send(ctx)).await
+    }
+
+    /// Tries to send a value without blocking. Fails if the buffer is full,
+    /// if other tasks are already waiting to send, or if the receiver was
+    /// dropped or closed.
+    pub fn try_send(&self, value: T) -> Result<(), TrySendError<T>> {
+        self.state.borrow_mut().try_send(value)
+    }
+
+    /// Returns whether the receiver was dropped or closed.
+    pub fn is_closed(&self) -> bool {
+        !self.state.borrow().receiver_alive
+    }
+
+    /// Returns the maximum number of values the buffer holds.
+    pub fn max_capacity(&self) -> usize {
+        self.state.borrow().capacity.unwrap_or(usize::MAX)
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        self.state.borrow_mut().senders += 1;
+        Self { state: self.state.clone() }
+    }
+}
+
+impl<T> fmt::Debug for Sender<T> {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        fmtr.pad("Sender { .. }")
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        self.state.borrow_mut().drop_sender();
+    }
+}
+
+struct SendSubscriber<'sender, T> {
+    state: &'sender RefCell<State<T>>,
+    value: Option<T>,
+    token: Option<Token>,
+}
+
+impl<'sender, T> SendSubscriber<'sender, T> {
+    fn poll_send(
+        &mut self,
+        ctx: &mut Context<'_>,
+    ) -> Poll<Result<(), SendError<T>>> {
+        let mut state = self.state.borrow_mut();
+        let value = self.value.take().expect("send polled after completion");
+        if !state.receiver_alive {
+            return Poll::Ready(Err(SendError(value)));
+        }
+        let first = state
+            .sends_on_hold
+            .first_key_value()
+            .map(|(token, _)| Some(*token) == self.token);
+        if state.has_room() && first.unwrap_or(true) {
+            if let Some(token) = self.token.take() {
+                state.sends_on_hold.remove(&token);
+            }
+            state.push(value);
+            state.forward_sends();
+            return Poll::Ready(Ok(()));
+        }
+        self.value = Some(value);
+        let token = match self.token {
+            Some(token) => token,
+            None => {
+                let token = state.new_token();
+                self.token = Some(token);
+                token
+            },
+        };
+        state.sends_on_hold.insert(token, ctx.waker().clone());
+        Poll::Pending
+    }
+}
+
+impl<'sender, T> Drop for SendSubscriber<'sender, T> {
+    fn drop(&mut self) {
+        if let Some(token) = self.token {
+            let mut state = self.state.borrow_mut();
+            state.sends_on_hold.remove(&token);
+            state.forward_sends();
+        }
+    }
+}
+
+/// The receiving half of a bounded [`mpsc`](self) channel.
+pub struct Receiver<T> {
+    state: Rc<RefCell<State<T>>>,
+}
+
+impl<T> Receiver<T> {
+    /// Receives the next value, waiting if none is buffered. Returns `None`
+    /// once the buffer is empty and all senders were dropped, or the receiver
+    /// was closed.
+    pub async fn recv(&mut self) -> Option<T> {
+        future::poll_fn(|ctx| self.poll_recv(ctx)).await
+    }
+
+    /// Tries to receive the next value without blocking.
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        self.state.borrow_mut().try_recv()
+    }
+
+    /// Polls for the next value, as [`Receiver::recv`] would.
+    pub fn poll_recv(&mut self, ctx: &mut Context<'_>) -> Poll<Option<T>> {
+        self.state.borrow_mut().poll_recv(ctx)
+    }
+
+    /// Closes the channel, preventing senders from sending further values.
+    /// Values already buffered can still be received.
+    pub fn close(&mut self) {
+        self.state.borrow_mut().close();
+    }
+}
+
+impl<T> fmt::Debug for Receiver<T> {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        fmtr.pad("Receiver { .. }")
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+#[cfg(feature = "stream")]
+impl<T> Stream for Receiver<T> {
+    type Item = T;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        ctx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        self.poll_recv(ctx)
+    }
+}
+
+/// The sending half of an unbounded [`mpsc`](self) channel. Can be cloned to
+/// send from many tasks.
+pub struct UnboundedSender<T> {
+    state: Rc<RefCell<State<T>>>,
+}
+
+impl<T> UnboundedSender<T> {
+    /// Sends a value without waiting. If the receiver was dropped or closed,
+    /// the value is given back as an error.
+    pub fn send(&self, value: T) -> Result<(), SendError<T>> {
+        self.state
+            .borrow_mut()
+            .try_send(value)
+            .map_err(|error| SendError(error.into_inner()))
+    }
+
+    /// Returns whether the receiver was dropped or closed.
+    pub fn is_closed(&self) -> bool {
+        !self.state.borrow().receiver_alive
+    }
+}
+
+impl<T> Clone for UnboundedSender<T> {
+    fn clone(&self) -> Self {
+        self.state.borrow_mut().senders += 1;
+        Self { state: self.state.clone() }
+    }
+}
+
+impl<T> fmt::Debug for UnboundedSender<T> {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        fmtr.pad("UnboundedSender { .. }")
+    }
+}
+
+impl<T> Drop for UnboundedSender<T> {
+    fn drop(&mut self) {
+        self.state.borrow_mut().drop_sender();
+    }
+}
+
+/// The receiving half of an unbounded [`mpsc`](self) channel.
+pub struct UnboundedReceiver<T> {
+    state: Rc<RefCell<State<T>>>,
+}
+
+impl<T> UnboundedReceiver<T> {
+    /// Receives the next value, waiting if none is buffered. Returns `None`
+    /// once the buffer is empty and all senders were dropped, or the receiver
+    /// was closed.
+    pub async fn recv(&mut self) -> Option<T> {
+        future::poll_fn(|ctx| self.poll_recv(ctx)).await
+    }
+
+    /// Tries to receive the next value without blocking.
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        self.state.borrow_mut().try_recv()
+    }
+
+    /// Polls for the next value, as [`UnboundedReceiver::recv`] would.
+    pub fn poll_recv(&mut self, ctx: &mut Context<'_>) -> Poll<Option<T>> {
+        self.state.borrow_mut().poll_recv(ctx)
+    }
+
+    /// Closes the channel, preventing senders from sending further values.
+    /// Values already buffered can still be received.
+    pub fn close(&mut self) {
+        self.state.borrow_mut().close();
+    }
+}
+
+impl<T> fmt::Debug for UnboundedReceiver<T> {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        fmtr.pad("UnboundedReceiver { .. }")
+    }
+}
+
+impl<T> Drop for UnboundedReceiver<T> {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+#[cfg(feature = "stream")]
+impl<T> Stream for UnboundedReceiver<T> {
+    type Item = T;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        ctx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        self.poll_recv(ctx)
+    }
+}
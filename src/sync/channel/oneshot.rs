@@ -0,0 +1,190 @@
+//! A channel to send a single value from a single producer to a single
+//! consumer.
+//!
+//! ```no_run
+//! use webio::{sync::channel::oneshot, task};
+//!
+//! # fn main() {
+//! # task::detach(async {
+//! let (sender, receiver) = oneshot::channel();
+//! task::spawn(async move {
+//!     sender.send(42).unwrap();
+//! });
+//! assert_eq!(receiver.await, Ok(42));
+//! # });
+//! # }
+//! ```
+
+use std::{
+    cell::RefCell,
+    error::Error,
+    fmt,
+    future::{self, Future},
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll, Waker},
+};
+
+/// The error returned by awaiting a [`Receiver`] when the [`Sender`] was
+/// dropped without sending a value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecvError;
+
+impl fmt::Display for RecvError {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmtr, "channel closed")
+    }
+}
+
+impl Error for RecvError {}
+
+/// The error returned by [`Receiver::try_recv`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TryRecvError {
+    /// No value was sent yet.
+    Empty,
+    /// The sender was dropped without sending a value, or the value was
+    /// already received.
+    Closed,
+}
+
+impl fmt::Display for TryRecvError {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Empty => write!(fmtr, "channel empty"),
+            Self::Closed => write!(fmtr, "channel closed"),
+        }
+    }
+}
+
+impl Error for TryRecvError {}
+
+#[derive(Debug)]
+struct State<T> {
+    value: Option<T>,
+    sender_alive: bool,
+    receiver_alive: bool,
+    receiver_waker: Option<Waker>,
+    sender_waker: Option<Waker>,
+}
+
+/// Creates a oneshot channel, returning the sending and the receiving halves.
+pub fn channel<T>() -> (Sender<T>, Receiver<T>) {
+    let state = Rc::new(RefCell::new(State {
+        value: None,
+        sender_alive: true,
+        receiver_alive: true,
+        receiver_waker: None,
+        sender_waker: None,
+    }));
+    (Sender { state: state.clone() }, Receiver { state })
+}
+
+/// The sending half of a [`oneshot`](self) channel.
+#[derive(Debug)]
+pub struct Sender<T> {
+    state: Rc<RefCell<State<T>>>,
+}
+
+impl<T> Sender<T> {
+    /// Sends the value, consuming the sender. If the receiver was dropped or
+    /// closed, the value is given back as an error.
+    pub fn send(self, value: T) -> Result<(), T> {
+        let mut state = self.state.borrow_mut();
+        if !state.receiver_alive {
+            return Err(value);
+        }
+        state.value = Some(value);
+        if let Some(waker) = state.receiver_waker.take() {
+            waker.wake();
+        }
+        Ok(())
+    }
+
+    /// Returns whether the receiver was dropped or closed.
+    pub fn is_closed(&self) -> bool {
+        !self.state.borrow().receiver_alive
+    }
+
+    /// Waits until the receiver is dropped or closed. Useful to stop
+    /// computing a value nobody is waiting for anymore.
+    pub async fn closed(&self) {
+        future::poll_fn(|ctx| {
+            let mut state = self.state.borrow_mut();
+            if state.receiver_alive {
+                state.sender_waker = Some(ctx.waker().clone());
+                Poll::Pending
+            } else {
+                Poll::Ready(())
+            }
+        })
+        .await
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut state = self.state.borrow_mut();
+        state.sender_alive = false;
+        if let Some(waker) = state.receiver_waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// The receiving half of a [`oneshot`](self) channel. The value is received
+/// by awaiting the receiver.
+#[derive(Debug)]
+pub struct Receiver<T> {
+    state: Rc<RefCell<State<T>>>,
+}
+
+impl<T> Receiver<T> {
+    /// Tries to receive the value without blocking.
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        let mut state = self.state.borrow_mut();
+        match state.value.take() {
+            Some(value) => {
+                state.sender_alive = false;
+                Ok(value)
+            },
+            None if state.sender_alive => Err(TryRecvError::Empty),
+            None => Err(TryRecvError::Closed),
+        }
+    }
+
+    /// Closes the channel, preventing the sender from sending a value. A
+    /// value sent before can still be received.
+    pub fn close(&mut self) {
+        let mut state = self.state.borrow_mut();
+        state.receiver_alive = false;
+        if let Some(waker) = state.sender_waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+impl<T> Future for Receiver<T> {
+    type Output = Result<T, RecvError>;
+
+    fn poll(
+        mut self: Pin<&mut Self>,
+        ctx: &mut Context<'_>,
+    ) -> Poll<Self::Output> {
+        match self.try_recv() {
+            Ok(value) => Poll::Ready(Ok(value)),
+            Err(TryRecvError::Closed) => Poll::Ready(Err(RecvError)),
+            Err(TryRecvError::Empty) => {
+                self.state.borrow_mut().receiver_waker =
+                    Some(ctx.waker().clone());
+                Poll::Pending
+            },
+        }
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
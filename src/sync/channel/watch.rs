@@ -0,0 +1,302 @@
+//! A channel holding a single value, updated by a single producer and
+//! observed by many consumers. Consumers only see the latest value, and they
+//! can wait until it changes.
+//!
+//! ```no_run
+//! use webio::{sync::channel::watch, task};
+//!
+//! # fn main() {
+//! # task::detach(async {
+//! let (sender, mut receiver) = watch::channel("idle");
+//!
+//! let handle = task::spawn(async move {
+//!     receiver.changed().await.unwrap();
+//!     assert_eq!(*receiver.borrow_and_update(), "loading");
+//! });
+//! sender.send("loading").unwrap();
+//! handle.await.unwrap();
+//! # });
+//! # }
+//! ```
+
+use std::{
+    cell::{Ref, RefCell},
+    collections::BTreeMap,
+    error::Error,
+    fmt,
+    future,
+    rc::Rc,
+    task::{Context, Poll, Waker},
+};
+
+#[cfg(feature = "stream")]
+use futures::stream::Stream;
+#[cfg(feature = "stream")]
+use std::pin::Pin;
+
+type Token = usize;
+
+/// The error returned by [`Sender::send`] when there are no receivers. The
+/// value is given back.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct SendError<T>(pub T);
+
+impl<T> fmt::Debug for SendError<T> {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        fmtr.pad("SendError { .. }")
+    }
+}
+
+impl<T> fmt::Display for SendError<T> {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmtr, "channel closed")
+    }
+}
+
+impl<T> Error for SendError<T> {}
+
+/// The error returned by [`Receiver::changed`] and [`Receiver::has_changed`]
+/// when the sender was dropped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecvError;
+
+impl fmt::Display for RecvError {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmtr, "channel closed")
+    }
+}
+
+impl Error for RecvError {}
+
+#[derive(Debug)]
+struct Shared<T> {
+    value: RefCell<T>,
+    state: RefCell<State>,
+}
+
+#[derive(Debug)]
+struct State {
+    version: u64,
+    sender_alive: bool,
+    receivers: BTreeMap<Token, Option<Waker>>,
+}
+
+impl State {
+    fn new_token(&self) -> Token {
+        self.receivers.last_key_value().map_or(0, |(token, _)| token + 1)
+    }
+
+    fn subscribe(&mut self) -> Token {
+        let token = self.new_token();
+        self.receivers.insert(token, None);
+        token
+    }
+
+    fn wake_all(&mut self) {
+        for waker in self.receivers.values_mut() {
+            if let Some(waker) = waker.take() {
+                waker.wake();
+            }
+        }
+    }
+}
+
+/// Creates a watch channel holding the given initial value, returning the
+/// sending and a first receiving half. More receivers are created by cloning
+/// receivers or through [`Sender::subscribe`].
+pub fn channel<T>(init: T) -> (Sender<T>, Receiver<T>) {
+    let mut state =
+        State { version: 0, sender_alive: true, receivers: BTreeMap::new() };
+    let token = state.subscribe();
+    let shared = Rc::new(Shared {
+        value: RefCell::new(init),
+        state: RefCell::new(state),
+    });
+    (Sender { shared: shared.clone() }, Receiver { shared, token, seen: 0 })
+}
+
+/// The sending half of a [`watch`](self) channel.
+pub struct Sender<T> {
+    shared: Rc<Shared<T>>,
+}
+
+impl<T> Sender<T> {
+    /// Replaces the value and notifies receivers of the change. If there are
+    /// no receivers, the value is given back as an error, and the held value
+    /// is left untouched.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is currently borrowed.
+    pub fn send(&self, value: T) -> Result<(), SendError<T>> {
+        if self.shared.state.borrow().receivers.is_empty() {
+            return Err(SendError(value));
+        }
+        self.send_replace(value);
+        Ok(())
+    }
+
+    /// Replaces the value and notifies receivers of the change, even if there
+    /// are none, returning the previous value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is currently borrowed.
+    pub fn send_replace(&self, value: T) -> T {
+        let previous = self.shared.value.replace(value);
+        self.notify();
+        previous
+    }
+
+    /// Modifies the value in place and notifies receivers of the change, even
+    /// if there are none.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the value is currently borrowed.
+    pub fn send_modify<F>(&self, modify: F)
+    where
+        F: FnOnce(&mut T),
+    {
+        modify(&mut self.shared.value.borrow_mut());
+        self.notify();
+    }
+
+    fn notify(&self) {
+        let mut state = self.shared.state.borrow_mut();
+        state.version += 1;
+        state.wake_all();
+    }
+
+    /// Borrows the current value. While the borrow is alive, the value cannot
+    /// be changed.
+    pub fn borrow(&self) -> Ref<'_, T> {
+        self.shared.value.borrow()
+    }
+
+    /// Creates a new receiver, which sees the current value as already seen.
+    pub fn subscribe(&self) -> Receiver<T> {
+        let mut state = self.shared.state.borrow_mut();
+        let token = state.subscribe();
+        Receiver { shared: self.shared.clone(), token, seen: state.version }
+    }
+
+    /// Returns how many receivers are alive.
+    pub fn receiver_count(&self) -> usize {
+        self.shared.state.borrow().receivers.len()
+    }
+
+    /// Returns whether all receivers were dropped.
+    pub fn is_closed(&self) -> bool {
+        self.receiver_count() == 0
+    }
+}
+
+impl<T> fmt::Debug for Sender<T> {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        fmtr.pad("Sender { .. }")
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut state = self.shared.state.borrow_mut();
+        state.sender_alive = false;
+        state.wake_all();
+    }
+}
+
+/// The receiving half of a [`watch`](self) channel. Can be cloned to observe
+/// the value from many tasks.
+pub struct Receiver<T> {
+    shared: Rc<Shared<T>>,
+    token: Token,
+    seen: u64,
+}
+
+impl<T> Receiver<T> {
+    /// Borrows the current value, without marking it as seen. While the borrow
+    /// is alive, the value cannot be changed.
+    pub fn borrow(&self) -> Ref<'_, T> {
+        self.shared.value.borrow()
+    }
+
+    /// Borrows the current value and marks it as seen. While the borrow is
+    /// alive, the value cannot be changed.
+    pub fn borrow_and_update(&mut self) -> Ref<'_, T> {
+        self.seen = self.shared.state.borrow().version;
+        self.shared.value.borrow()
+    }
+
+    /// Returns whether the value changed since it was last seen. Fails if the
+    /// sender was dropped.
+    pub fn has_changed(&self) -> Result<bool, RecvError> {
+        let state = self.shared.state.borrow();
+        if state.sender_alive {
+            Ok(state.version != self.seen)
+        } else {
+            Err(RecvError)
+        }
+    }
+
+    /// Waits until the value changes from the one last seen, marking the new
+    /// value as seen. Completes right away if the value already changed.
+    /// Fails if the sender is dropped without a change left unseen.
+    pub async fn changed(&mut self) -> Result<(), RecvError> {
+        future::poll_fn(|ctx| self.poll_changed(ctx)).await
+    }
+
+    fn poll_changed(
+        &mut self,
+        ctx: &mut Context<'_>,
+    ) -> Poll<Result<(), RecvError>> {
+        let mut state = self.shared.state.borrow_mut();
+        if state.version != self.seen {
+            self.seen = state.version;
+            Poll::Ready(Ok(()))
+        } else if !state.sender_alive {
+            Poll::Ready(Err(RecvError))
+        } else {
+            state.receivers.insert(self.token, Some(ctx.waker().clone()));
+            Poll::Pending
+        }
+    }
+}
+
+impl<T> Clone for Receiver<T> {
+    fn clone(&self) -> Self {
+        let token = self.shared.state.borrow_mut().subscribe();
+        Self { shared: self.shared.clone(), token, seen: self.seen }
+    }
+}
+
+impl<T> fmt::Debug for Receiver<T> {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        fmtr.pad("Receiver { .. }")
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.shared.state.borrow_mut().receivers.remove(&self.token);
+    }
+}
+
+/// Yields a clone of the value each time it changes, and ends when the sender
+/// is dropped.
+#[cfg(feature = "stream")]
+impl<T> Stream for Receiver<T>
+where
+    T: Clone,
+{
+    type Item = T;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        ctx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        self.poll_changed(ctx).map(|result| {
+            result.ok().map(|()| self.shared.value.borrow().clone())
+        })
+    }
+}
@@ -5,6 +5,7 @@ use std::{
     future::Future,
     ops::{Deref, DerefMut},
     pin::Pin,
+    rc::Rc,
     task::{Context, Poll, Waker},
 };
 
@@ -21,6 +22,16 @@ impl Queue {
         Self::default()
     }
 
+    fn with<F, A>(cell: &Cell<Self>, visitor: F) -> A
+    where
+        F: FnOnce(&mut Self) -> A,
+    {
+        let mut queue = cell.take();
+        let output = visitor(&mut queue);
+        cell.set(queue);
+        output
+    }
+
     fn new_token(&self) -> Token {
         self.on_hold
             .last_key_value()
@@ -78,10 +89,7 @@ impl<T> Mutex<T> {
     where
         F: FnOnce(&mut Queue) -> A,
     {
-        let mut queue = self.queue.take();
-        let output = visitor(&mut queue);
-        self.queue.set(queue);
-        output
+        Queue::with(&self.queue, visitor)
     }
 
     /// Creates a mutex from initial protected data.
@@ -103,7 +111,7 @@ impl<T> Mutex<T> {
     /// Tries to lock without blocking. If already locked, returns `None`,
     /// otherwise, locks and returns a guard. While the guard is not dropped,
     /// the mutex remains locked.
-    pub fn try_lock(&self) -> Option<MutexGuard<T>> {
+    pub fn try_lock(&self) -> Option<MutexGuard<'_, T>> {
         self.with_queue(|queue| {
             if queue.try_acquire().is_some() {
                 Some(self.do_lock())
@@ -115,15 +123,58 @@ impl<T> Mutex<T> {
 
     /// Locks, waiting if already locked. When the lock is acquired, returns a
     /// guard. While the guard is not dropped, the mutex remains locked.
-    pub async fn lock(&self) -> MutexGuard<T> {
+    pub async fn lock(&self) -> MutexGuard<'_, T> {
         let subscriber =
             Subscriber { mutex: self, state: SubscriberState::NotSubscribed };
         subscriber.await;
         self.do_lock()
     }
 
-    fn do_lock(&self) -> MutexGuard<T> {
-        MutexGuard { mutex: self, ref_mut: self.data.borrow_mut() }
+    fn do_lock(&self) -> MutexGuard<'_, T> {
+        MutexGuard {
+            ref_mut: self.data.borrow_mut(),
+            release: Release { queue: &self.queue },
+        }
+    }
+
+    /// Tries to lock without blocking, through a reference-counted pointer.
+    /// If already locked, returns `None`, otherwise, locks and returns an
+    /// owned guard. Unlike [`MutexGuard`], the owned guard does not borrow the
+    /// mutex, and so it can be moved into spawned tasks or stored.
+    pub fn try_lock_owned(self: Rc<Self>) -> Option<OwnedMutexGuard<T>> {
+        if self.with_queue(|queue| queue.try_acquire().is_some()) {
+            Some(OwnedMutexGuard { mutex: self })
+        } else {
+            None
+        }
+    }
+
+    /// Locks, waiting if already locked, through a reference-counted pointer.
+    /// When the lock is acquired, returns an owned guard. Unlike
+    /// [`MutexGuard`], the owned guard does not borrow the mutex, and so it
+    /// can be moved into spawned tasks or stored.
+    ///
+    /// ```no_run
+    /// use std::rc::Rc;
+    /// use webio::{sync::Mutex, task};
+    ///
+    /// # fn main() {
+    /// # task::detach(async {
+    /// let mutex = Rc::new(Mutex::new(0));
+    /// let mut guard = mutex.clone().lock_owned().await;
+    /// let handle = task::spawn(async move {
+    ///     *guard += 1;
+    /// });
+    /// handle.await.unwrap();
+    /// assert_eq!(*mutex.lock().await, 1);
+    /// # });
+    /// # }
+    /// ```
+    pub async fn lock_owned(self: Rc<Self>) -> OwnedMutexGuard<T> {
+        let subscriber =
+            Subscriber { mutex: &self, state: SubscriberState::NotSubscribed };
+        subscriber.await;
+        OwnedMutexGuard { mutex: self }
     }
 }
 
@@ -142,39 +193,167 @@ where
 {
     fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
         self.with_queue(|queue| {
-            fmtr.debug_struct("Mutex")
-                .field("data", &self.data)
-                .field("queue", &queue)
-                .finish()
+            let mut debug = fmtr.debug_struct("Mutex");
+            // Owned guards do not borrow the data, so it must not be read
+            // while locked.
+            if queue.owner.is_some() {
+                debug.field("data", &format_args!("<locked>"));
+            } else {
+                debug.field("data", &self.data);
+            }
+            debug.field("queue", &queue).finish()
         })
     }
 }
 
+/// Releases the lock of a [`Mutex`] when dropped.
+struct Release<'mutex> {
+    queue: &'mutex Cell<Queue>,
+}
+
+impl<'mutex> fmt::Debug for Release<'mutex> {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        fmtr.pad("Release { .. }")
+    }
+}
+
+impl<'mutex> Drop for Release<'mutex> {
+    fn drop(&mut self) {
+        Queue::with(self.queue, Queue::release);
+    }
+}
+
 /// A guard of a current locking on a [`Mutex`]. Can be derreferenced to get
 /// access to protected data.
 #[derive(Debug)]
 pub struct MutexGuard<'mutex, T> {
-    mutex: &'mutex Mutex<T>,
     ref_mut: RefMut<'mutex, T>,
+    release: Release<'mutex>,
+}
+
+impl<'mutex, T> MutexGuard<'mutex, T> {
+    /// Projects the guard into a component of the protected data, such as a
+    /// field. The mutex remains locked until the returned guard is dropped.
+    /// This is an associated function, called as `MutexGuard::map(...)`, so it
+    /// does not conflict with methods of the protected data.
+    pub fn map<U, F>(guard: Self, visitor: F) -> MappedMutexGuard<'mutex, U>
+    where
+        U: ?Sized,
+        F: FnOnce(&mut T) -> &mut U,
+    {
+        MappedMutexGuard {
+            ref_mut: RefMut::map(guard.ref_mut, visitor),
+            release: guard.release,
+        }
+    }
 }
 
 impl<'mutex, T> Deref for MutexGuard<'mutex, T> {
     type Target = T;
 
     fn deref(&self) -> &Self::Target {
-        &*self.ref_mut
+        &self.ref_mut
     }
 }
 
 impl<'mutex, T> DerefMut for MutexGuard<'mutex, T> {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut *self.ref_mut
+        &mut self.ref_mut
+    }
+}
+
+/// A guard of a current locking on a [`Mutex`], projected into a component of
+/// protected data through [`MutexGuard::map`].
+#[derive(Debug)]
+pub struct MappedMutexGuard<'mutex, T>
+where
+    T: ?Sized,
+{
+    ref_mut: RefMut<'mutex, T>,
+    release: Release<'mutex>,
+}
+
+impl<'mutex, T> MappedMutexGuard<'mutex, T>
+where
+    T: ?Sized,
+{
+    /// Projects the guard further into a component of the projected data.
+    pub fn map<U, F>(guard: Self, visitor: F) -> MappedMutexGuard<'mutex, U>
+    where
+        U: ?Sized,
+        F: FnOnce(&mut T) -> &mut U,
+    {
+        MappedMutexGuard {
+            ref_mut: RefMut::map(guard.ref_mut, visitor),
+            release: guard.release,
+        }
+    }
+}
+
+impl<'mutex, T> Deref for MappedMutexGuard<'mutex, T>
+where
+    T: ?Sized,
+{
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.ref_mut
+    }
+}
+
+impl<'mutex, T> DerefMut for MappedMutexGuard<'mutex, T>
+where
+    T: ?Sized,
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.ref_mut
+    }
+}
+
+/// An owned guard of a current locking on a [`Mutex`], created by
+/// [`Mutex::lock_owned`]. Can be derreferenced to get access to protected
+/// data. It keeps the mutex alive, and it is `'static` if the data is.
+pub struct OwnedMutexGuard<T> {
+    mutex: Rc<Mutex<T>>,
+}
+
+impl<T> OwnedMutexGuard<T> {
+    /// The mutex this guard locks.
+    pub fn mutex(&self) -> &Rc<Mutex<T>> {
+        &self.mutex
+    }
+}
+
+impl<T> Deref for OwnedMutexGuard<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        // SAFETY: while this guard is alive, the queue grants no other guard,
+        // and the data is never accessed other than through guards.
+        unsafe { &*self.mutex.data.as_ptr() }
+    }
+}
+
+impl<T> DerefMut for OwnedMutexGuard<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        // SAFETY: while this guard is alive, the queue grants no other guard,
+        // and the data is never accessed other than through guards.
+        unsafe { &mut *self.mutex.data.as_ptr() }
+    }
+}
+
+impl<T> fmt::Debug for OwnedMutexGuard<T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        fmtr.debug_struct("OwnedMutexGuard").field("data", &**self).finish()
     }
 }
 
-impl<'mutex, T> Drop for MutexGuard<'mutex, T> {
+impl<T> Drop for OwnedMutexGuard<T> {
     fn drop(&mut self) {
-        self.mutex.with_queue(|queue| queue.release());
+        self.mutex.with_queue(Queue::release);
     }
 }
 
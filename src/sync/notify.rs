@@ -0,0 +1,206 @@
+use std::{
+    cell::Cell,
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll, Waker},
+};
+
+type Token = usize;
+
+#[derive(Debug, Clone, Default)]
+struct Queue {
+    permit: bool,
+    generation: usize,
+    next_token: Token,
+    notified: BTreeSet<Token>,
+    on_hold: BTreeMap<Token, Waker>,
+}
+
+impl Queue {
+    fn new() -> Self {
+        Self::default()
+    }
+
+    fn new_token(&mut self) -> Token {
+        let token = self.next_token;
+        self.next_token = self.next_token.wrapping_add(1);
+        token
+    }
+
+    fn notify_one(&mut self) {
+        if let Some((token, waker)) = self.on_hold.pop_first() {
+            self.notified.insert(token);
+            waker.wake();
+        } else {
+            self.permit = true;
+        }
+    }
+
+    fn notify_waiters(&mut self) {
+        self.generation = self.generation.wrapping_add(1);
+        while let Some((_, waker)) = self.on_hold.pop_first() {
+            waker.wake();
+        }
+    }
+
+    fn cancel(&mut self, token: Token) {
+        if self.notified.remove(&token) {
+            self.notify_one();
+        } else {
+            self.on_hold.remove(&token);
+        }
+    }
+}
+
+/// Notifies waiting tasks of an event, without carrying any data. Behaves much
+/// like [`tokio::sync::Notify`], but designed for WASM (single-thread, thus
+/// this struct is Unsync). Tasks waiting through [`Notify::notified`] are
+/// woken by [`Notify::notify_one`] in the order they started waiting.
+///
+/// ```no_run
+/// use std::rc::Rc;
+/// use webio::{sync::Notify, task};
+///
+/// # fn main() {
+/// # task::detach(async {
+/// let notify = Rc::new(Notify::new());
+/// let handle = task::spawn({
+///     let notify = notify.clone();
+///     async move {
+///         notify.notified().await;
+///         println!("received notification");
+///     }
+/// });
+///
+/// println!("sending notification");
+/// notify.notify_one();
+/// handle.await.unwrap();
+/// # });
+/// # }
+/// ```
+pub struct Notify {
+    queue: Cell<Queue>,
+}
+
+impl Notify {
+    fn with_queue<F, A>(&self, visitor: F) -> A
+    where
+        F: FnOnce(&mut Queue) -> A,
+    {
+        let mut queue = self.queue.take();
+        let output = visitor(&mut queue);
+        self.queue.set(queue);
+        output
+    }
+
+    /// Creates a notify without any stored permit.
+    pub fn new() -> Self {
+        Self { queue: Cell::new(Queue::new()) }
+    }
+
+    /// Waits for a notification. This is an asynchronous function.
+    ///
+    /// The returned future completes after a call to [`Notify::notify_one`]
+    /// that selects it, or consumes the stored permit if there is one. It also
+    /// completes after any call to [`Notify::notify_waiters`] made after it was
+    /// created, even if it was not polled yet.
+    pub fn notified(&self) -> Notified<'_> {
+        let generation = self.with_queue(|queue| queue.generation);
+        Notified { notify: self, generation, state: NotifiedState::NotWaiting }
+    }
+
+    /// Notifies the task that has been waiting the longest. If no task is
+    /// waiting, a permit is stored, and the next call to
+    /// [`Notify::notified`] completes right away. At most one permit is
+    /// stored.
+    pub fn notify_one(&self) {
+        self.with_queue(Queue::notify_one);
+    }
+
+    /// Notifies all tasks currently waiting. Unlike [`Notify::notify_one`], no
+    /// permit is stored if no task is waiting.
+    pub fn notify_waiters(&self) {
+        self.with_queue(Queue::notify_waiters);
+    }
+}
+
+impl Default for Notify {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Notify {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        self.with_queue(|queue| {
+            fmtr.debug_struct("Notify").field("queue", &queue).finish()
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum NotifiedState {
+    NotWaiting,
+    Waiting(Token),
+    Done,
+}
+
+/// A future waiting for a notification, created by [`Notify::notified`].
+#[derive(Debug)]
+pub struct Notified<'notify> {
+    notify: &'notify Notify,
+    generation: usize,
+    state: NotifiedState,
+}
+
+impl<'notify> Future for Notified<'notify> {
+    type Output = ();
+
+    fn poll(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Self::Output> {
+        let this = &mut *self;
+        match this.state {
+            NotifiedState::Done => Poll::Ready(()),
+            NotifiedState::Waiting(token) => this.notify.with_queue(|queue| {
+                if queue.notified.remove(&token)
+                    || queue.generation != this.generation
+                {
+                    queue.on_hold.remove(&token);
+                    this.state = NotifiedState::Done;
+                    Poll::Ready(())
+                } else {
+                    if let Some(waker) = queue.on_hold.get_mut(&token) {
+                        waker.clone_from(cx.waker());
+                    }
+                    Poll::Pending
+                }
+            }),
+            NotifiedState::NotWaiting => this.notify.with_queue(|queue| {
+                if queue.generation != this.generation || queue.permit {
+                    if queue.generation == this.generation {
+                        queue.permit = false;
+                    }
+                    this.state = NotifiedState::Done;
+                    Poll::Ready(())
+                } else {
+                    let token = queue.new_token();
+                    queue.on_hold.insert(token, cx.waker().clone());
+                    this.state = NotifiedState::Waiting(token);
+                    Poll::Pending
+                }
+            }),
+        }
+    }
+}
+
+impl<'notify> Drop for Notified<'notify> {
+    fn drop(&mut self) {
+        if let NotifiedState::Waiting(token) = self.state {
+            self.notify.with_queue(|queue| queue.cancel(token));
+        }
+    }
+}
@@ -3,8 +3,10 @@ use std::{
     collections::{BTreeMap, BTreeSet},
     fmt,
     future::Future,
+    mem,
     ops::{Deref, DerefMut},
     pin::Pin,
+    rc::Rc,
     task::{Context, Poll, Waker},
 };
 
@@ -14,7 +16,10 @@ type Token = usize;
 struct Queue {
     write_owner: Option<Token>,
     read_owners: BTreeSet<Token>,
+    upgradeable_owner: Option<Token>,
+    upgrade_on_hold: Option<Waker>,
     reads_on_hold: BTreeMap<Token, Waker>,
+    upgradeable_reads_on_hold: BTreeMap<Token, Waker>,
     writes_on_hold: BTreeMap<Token, Waker>,
 }
 
@@ -23,25 +28,48 @@ impl Queue {
         Self::default()
     }
 
+    fn with<F, A>(cell: &Cell<Self>, visitor: F) -> A
+    where
+        F: FnOnce(&mut Self) -> A,
+    {
+        let mut queue = cell.take();
+        let output = visitor(&mut queue);
+        cell.set(queue);
+        output
+    }
+
     fn new_token(&self) -> Token {
         let max_write_owner = self.write_owner;
         let max_read_owner = self.read_owners.iter().next_back().copied();
         let max_write_on_hold = self.writes_on_hold.keys().next_back().copied();
         let max_read_on_hold = self.reads_on_hold.keys().next_back().copied();
+        let max_upgradeable_read_on_hold =
+            self.upgradeable_reads_on_hold.keys().next_back().copied();
         max_write_owner
             .max(max_read_owner)
             .max(max_write_on_hold)
             .max(max_read_on_hold)
+            .max(max_upgradeable_read_on_hold)
             .map_or(0, |token| token + 1)
     }
 
-    fn acquire_read(&mut self, waker: Waker, token: Token) {
-        if self.write_owner.is_some()
+    fn read_blocked(&self, token: Token) -> bool {
+        self.write_owner.is_some()
+            || self.upgrade_on_hold.is_some()
             || self
                 .writes_on_hold
                 .last_key_value()
                 .is_some_and(|(max, _)| token > *max)
-        {
+    }
+
+    fn upgradeable_read_blocked(&self, token: Token) -> bool {
+        self.upgradeable_owner.is_some()
+            || !self.upgradeable_reads_on_hold.is_empty()
+            || self.read_blocked(token)
+    }
+
+    fn acquire_read(&mut self, waker: Waker, token: Token) {
+        if self.read_blocked(token) {
             self.reads_on_hold.insert(token, waker);
         } else {
             self.read_owners.insert(token);
@@ -49,6 +77,16 @@ impl Queue {
         }
     }
 
+    fn acquire_upgradeable_read(&mut self, waker: Waker, token: Token) {
+        if self.upgradeable_read_blocked(token) {
+            self.upgradeable_reads_on_hold.insert(token, waker);
+        } else {
+            self.read_owners.insert(token);
+            self.upgradeable_owner = Some(token);
+            waker.wake();
+        }
+    }
+
     fn acquire_write(&mut self, waker: Waker, token: Token) {
         if self.write_owner.is_some() || !self.read_owners.is_empty() {
             self.writes_on_hold.insert(token, waker);
@@ -60,15 +98,21 @@ impl Queue {
 
     fn try_acquire_read(&mut self) -> Option<Token> {
         let token = self.new_token();
-        if self.write_owner.is_some()
-            || self
-                .writes_on_hold
-                .last_key_value()
-                .is_some_and(|(max, _)| token > *max)
-        {
+        if self.read_blocked(token) {
+            None
+        } else {
+            self.read_owners.insert(token);
+            Some(token)
+        }
+    }
+
+    fn try_acquire_upgradeable_read(&mut self) -> Option<Token> {
+        let token = self.new_token();
+        if self.upgradeable_read_blocked(token) {
             None
         } else {
             self.read_owners.insert(token);
+            self.upgradeable_owner = Some(token);
             Some(token)
         }
     }
@@ -86,7 +130,15 @@ impl Queue {
     fn release_read(&mut self, token: Token) {
         self.read_owners.remove(&token);
 
-        if self.read_owners.is_empty() {
+        if self.upgradeable_owner == Some(token) {
+            self.upgradeable_owner = None;
+            self.upgrade_on_hold = None;
+            self.forward_reads(self.first_write_on_hold());
+        }
+
+        if self.upgrade_on_hold.is_some() {
+            self.forward_upgrade();
+        } else if self.read_owners.is_empty() {
             if let Some((write_token, write_waker)) =
                 self.writes_on_hold.pop_first()
             {
@@ -115,11 +167,52 @@ impl Queue {
         }
     }
 
+    fn downgrade_write(&mut self) -> Token {
+        let token = self.write_owner.take().expect("write-locked");
+        self.read_owners.insert(token);
+        self.forward_reads(self.first_write_on_hold());
+        token
+    }
+
+    fn downgrade_upgradeable_read(&mut self) {
+        self.upgradeable_owner = None;
+        self.forward_reads(self.first_write_on_hold());
+    }
+
+    fn request_upgrade(&mut self, waker: Waker) {
+        self.upgrade_on_hold = Some(waker);
+        self.forward_upgrade();
+    }
+
+    fn try_upgrade(&mut self, token: Token) -> bool {
+        if self.read_owners.len() == 1 {
+            self.read_owners.remove(&token);
+            self.upgradeable_owner = None;
+            self.write_owner = Some(token);
+            true
+        } else {
+            false
+        }
+    }
+
+    fn forward_upgrade(&mut self) {
+        if let Some(token) = self.upgradeable_owner {
+            if self.try_upgrade(token) {
+                if let Some(waker) = self.upgrade_on_hold.take() {
+                    waker.wake();
+                }
+            }
+        }
+    }
+
     fn cancel_read(&mut self, token: Token) {
         if self.read_owners.contains(&token) {
             self.release_read(token);
         } else {
             self.reads_on_hold.remove(&token);
+            if self.upgradeable_reads_on_hold.remove(&token).is_some() {
+                self.forward_reads(self.first_write_on_hold());
+            }
         }
     }
 
@@ -128,13 +221,28 @@ impl Queue {
             self.release_write();
         } else {
             self.writes_on_hold.remove(&token);
-            self.forward_reads(
-                self.writes_on_hold.first_key_value().map(|(token, _)| *token),
-            );
+            self.forward_reads(self.first_write_on_hold());
         }
     }
 
+    fn cancel_upgrade(&mut self, token: Token) {
+        if self.write_owner == Some(token) {
+            self.release_write();
+        } else {
+            self.upgrade_on_hold = None;
+            self.release_read(token);
+        }
+    }
+
+    fn first_write_on_hold(&self) -> Option<Token> {
+        self.writes_on_hold.first_key_value().map(|(token, _)| *token)
+    }
+
     fn forward_reads(&mut self, write_token: Option<Token>) {
+        if self.write_owner.is_some() || self.upgrade_on_hold.is_some() {
+            return;
+        }
+
         while let Some((read_token, read_waker)) =
             self.reads_on_hold.pop_first()
         {
@@ -145,6 +253,17 @@ impl Queue {
             self.read_owners.insert(read_token);
             read_waker.wake();
         }
+
+        if self.upgradeable_owner.is_none() {
+            if let Some(entry) = self.upgradeable_reads_on_hold.first_entry() {
+                if write_token.is_none_or(|token| *entry.key() < token) {
+                    let (read_token, read_waker) = entry.remove_entry();
+                    self.read_owners.insert(read_token);
+                    self.upgradeable_owner = Some(read_token);
+                    read_waker.wake();
+                }
+            }
+        }
     }
 }
 
@@ -163,10 +282,7 @@ impl<T> RwLock<T> {
     where
         F: FnOnce(&mut Queue) -> A,
     {
-        let mut queue = self.queue.take();
-        let output = visitor(&mut queue);
-        self.queue.set(queue);
-        output
+        Queue::with(&self.queue, visitor)
     }
 
     /// Creates a read-write-lock from initial protected data.
@@ -188,35 +304,96 @@ impl<T> RwLock<T> {
     /// Tries to read-lock without blocking. If write-locked, returns `None`,
     /// otherwise, locks and returns a guard. While the guard is not dropped,
     /// the lock remains locked.
-    pub fn try_read(&self) -> Option<ReadGuard<T>> {
+    pub fn try_read(&self) -> Option<ReadGuard<'_, T>> {
         self.with_queue(|queue| {
-            if let Some(token) = queue.try_acquire_read() {
-                Some(self.do_read(token))
-            } else {
-                None
-            }
+            queue.try_acquire_read().map(|token| self.do_read(token))
         })
     }
 
     /// Read-locks, waiting if write-locked. When the lock is acquired, returns
     /// a guard. While the guard is not dropped, the lock remains locked.
-    pub async fn read(&self) -> ReadGuard<T> {
+    pub async fn read(&self) -> ReadGuard<'_, T> {
         let subscriber = ReadSubscriber {
             rw_lock: self,
+            upgradeable: false,
             state: ReadSubscriberState::NotSubscribed,
         };
         let token = subscriber.await;
         self.do_read(token)
     }
 
-    fn do_read(&self, token: Token) -> ReadGuard<T> {
-        ReadGuard { rw_lock: self, token, ref_borrow: self.data.borrow() }
+    fn do_read(&self, token: Token) -> ReadGuard<'_, T> {
+        ReadGuard {
+            ref_borrow: self.data.borrow(),
+            release: ReadRelease { queue: &self.queue, token },
+        }
+    }
+
+    /// Tries to upgradeable-read-lock without blocking. If write-locked, or if
+    /// already upgradeable-read-locked, returns `None`, otherwise, locks and
+    /// returns a guard. While the guard is not dropped, the lock remains
+    /// locked.
+    pub fn try_upgradeable_read(&self) -> Option<UpgradeableReadGuard<'_, T>> {
+        self.with_queue(|queue| {
+            queue
+                .try_acquire_upgradeable_read()
+                .map(|token| self.do_upgradeable_read(token))
+        })
+    }
+
+    /// Upgradeable-read-locks, waiting if write-locked, or if already
+    /// upgradeable-read-locked. When the lock is acquired, returns a guard.
+    /// While the guard is not dropped, the lock remains locked.
+    ///
+    /// An upgradeable read-locking coexists with normal read-lockings, but not
+    /// with another upgradeable one, and it can be atomically upgraded into a
+    /// write-locking through [`UpgradeableReadGuard::upgrade`], i.e. no other
+    /// write-locking can happen between reading and writing.
+    ///
+    /// ```no_run
+    /// use std::collections::HashMap;
+    /// use webio::{
+    ///     sync::{RwLock, UpgradeableReadGuard},
+    ///     task,
+    /// };
+    ///
+    /// # fn main() {
+    /// # task::detach(async {
+    /// let cache = RwLock::new(HashMap::new());
+    /// let guard = cache.upgradeable_read().await;
+    /// let guard = if guard.contains_key("answer") {
+    ///     UpgradeableReadGuard::downgrade(guard)
+    /// } else {
+    ///     let mut guard = UpgradeableReadGuard::upgrade(guard).await;
+    ///     guard.insert("answer", 42);
+    ///     guard.downgrade()
+    /// };
+    /// assert_eq!(guard.get("answer"), Some(&42));
+    /// # });
+    /// # }
+    /// ```
+    pub async fn upgradeable_read(&self) -> UpgradeableReadGuard<'_, T> {
+        let subscriber = ReadSubscriber {
+            rw_lock: self,
+            upgradeable: true,
+            state: ReadSubscriberState::NotSubscribed,
+        };
+        let token = subscriber.await;
+        self.do_upgradeable_read(token)
+    }
+
+    fn do_upgradeable_read(&self, token: Token) -> UpgradeableReadGuard<'_, T> {
+        UpgradeableReadGuard {
+            rw_lock: self,
+            ref_borrow: self.data.borrow(),
+            release: ReadRelease { queue: &self.queue, token },
+        }
     }
 
     /// Tries to write-lock without blocking. If already write-locked, or if
     /// read-locked, returns `None`, otherwise, locks and returns a guard.
     /// While the guard is not dropped, the lock remains locked.
-    pub fn try_write(&self) -> Option<WriteGuard<T>> {
+    pub fn try_write(&self) -> Option<WriteGuard<'_, T>> {
         self.with_queue(|queue| {
             if queue.try_acquire_write().is_some() {
                 Some(self.do_write())
@@ -229,7 +406,7 @@ impl<T> RwLock<T> {
     /// Write-locks, waiting if already write-locked, or if read-locked. When
     /// the lock is acquired, returns a guard. While the guard is not
     /// dropped, the lock remains locked.
-    pub async fn write(&self) -> WriteGuard<T> {
+    pub async fn write(&self) -> WriteGuard<'_, T> {
         let subscriber = WriteSubscriber {
             rw_lock: self,
             state: WriteSubscriberState::NotSubscribed,
@@ -238,8 +415,75 @@ impl<T> RwLock<T> {
         self.do_write()
     }
 
-    fn do_write(&self) -> WriteGuard<T> {
-        WriteGuard { rw_lock: self, ref_mut: self.data.borrow_mut() }
+    fn do_write(&self) -> WriteGuard<'_, T> {
+        WriteGuard {
+            rw_lock: self,
+            ref_mut: self.data.borrow_mut(),
+            release: WriteRelease { queue: &self.queue },
+        }
+    }
+
+    /// Tries to read-lock without blocking, through a reference-counted
+    /// pointer. If write-locked, returns `None`, otherwise, locks and returns
+    /// an owned guard. Unlike [`ReadGuard`], the owned guard does not borrow
+    /// the lock, and so it can be moved into spawned tasks or stored.
+    pub fn try_read_owned(self: Rc<Self>) -> Option<OwnedReadGuard<T>> {
+        let token = self.with_queue(Queue::try_acquire_read)?;
+        Some(OwnedReadGuard { rw_lock: self, token })
+    }
+
+    /// Read-locks, waiting if write-locked, through a reference-counted
+    /// pointer. When the lock is acquired, returns an owned guard. Unlike
+    /// [`ReadGuard`], the owned guard does not borrow the lock, and so it can
+    /// be moved into spawned tasks or stored.
+    pub async fn read_owned(self: Rc<Self>) -> OwnedReadGuard<T> {
+        let subscriber = ReadSubscriber {
+            rw_lock: &self,
+            upgradeable: false,
+            state: ReadSubscriberState::NotSubscribed,
+        };
+        let token = subscriber.await;
+        OwnedReadGuard { rw_lock: self, token }
+    }
+
+    /// Tries to write-lock without blocking, through a reference-counted
+    /// pointer. If already write-locked, or if read-locked, returns `None`,
+    /// otherwise, locks and returns an owned guard. Unlike [`WriteGuard`], the
+    /// owned guard does not borrow the lock, and so it can be moved into
+    /// spawned tasks or stored.
+    pub fn try_write_owned(self: Rc<Self>) -> Option<OwnedWriteGuard<T>> {
+        self.with_queue(Queue::try_acquire_write)?;
+        Some(OwnedWriteGuard { rw_lock: self })
+    }
+
+    /// Write-locks, waiting if already write-locked, or if read-locked,
+    /// through a reference-counted pointer. When the lock is acquired, returns
+    /// an owned guard. Unlike [`WriteGuard`], the owned guard does not borrow
+    /// the lock, and so it can be moved into spawned tasks or stored.
+    ///
+    /// ```no_run
+    /// use std::rc::Rc;
+    /// use webio::{sync::RwLock, task};
+    ///
+    /// # fn main() {
+    /// # task::detach(async {
+    /// let rw_lock = Rc::new(RwLock::new(Vec::new()));
+    /// let mut guard = rw_lock.clone().write_owned().await;
+    /// let handle = task::spawn(async move {
+    ///     guard.push("loaded");
+    /// });
+    /// handle.await.unwrap();
+    /// assert_eq!(*rw_lock.read().await, ["loaded"]);
+    /// # });
+    /// # }
+    /// ```
+    pub async fn write_owned(self: Rc<Self>) -> OwnedWriteGuard<T> {
+        let subscriber = WriteSubscriber {
+            rw_lock: &self,
+            state: WriteSubscriberState::NotSubscribed,
+        };
+        subscriber.await;
+        OwnedWriteGuard { rw_lock: self }
     }
 }
 
@@ -258,34 +502,234 @@ where
 {
     fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
         self.with_queue(|queue| {
-            fmtr.debug_struct("RwLock")
-                .field("data", &self.data)
-                .field("queue", &queue)
-                .finish()
+            let mut debug = fmtr.debug_struct("RwLock");
+            // Owned guards do not borrow the data, so it must not be read
+            // while write-locked.
+            if queue.write_owner.is_some() {
+                debug.field("data", &format_args!("<locked>"));
+            } else {
+                debug.field("data", &self.data);
+            }
+            debug.field("queue", &queue).finish()
         })
     }
 }
 
+/// Releases a read-locking of a [`RwLock`] when dropped.
+struct ReadRelease<'rw> {
+    queue: &'rw Cell<Queue>,
+    token: Token,
+}
+
+impl<'rw> fmt::Debug for ReadRelease<'rw> {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        fmtr.pad("ReadRelease { .. }")
+    }
+}
+
+impl<'rw> Drop for ReadRelease<'rw> {
+    fn drop(&mut self) {
+        Queue::with(self.queue, |queue| queue.release_read(self.token));
+    }
+}
+
+/// Releases a write-locking of a [`RwLock`] when dropped.
+struct WriteRelease<'rw> {
+    queue: &'rw Cell<Queue>,
+}
+
+impl<'rw> fmt::Debug for WriteRelease<'rw> {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        fmtr.pad("WriteRelease { .. }")
+    }
+}
+
+impl<'rw> Drop for WriteRelease<'rw> {
+    fn drop(&mut self) {
+        Queue::with(self.queue, Queue::release_write);
+    }
+}
+
 /// A guard of a current read/shared-locking on a [`RwLock`]. Can be
 /// derreferenced to get read access to protected data.
 #[derive(Debug)]
 pub struct ReadGuard<'rw, T> {
-    rw_lock: &'rw RwLock<T>,
-    token: Token,
     ref_borrow: Ref<'rw, T>,
+    release: ReadRelease<'rw>,
+}
+
+impl<'rw, T> ReadGuard<'rw, T> {
+    /// Projects the guard into a component of the protected data, such as a
+    /// field. The lock remains read-locked until the returned guard is
+    /// dropped. This is an associated function, called as
+    /// `ReadGuard::map(...)`, so it does not conflict with methods of the
+    /// protected data.
+    pub fn map<U, F>(guard: Self, visitor: F) -> MappedReadGuard<'rw, U>
+    where
+        U: ?Sized,
+        F: FnOnce(&T) -> &U,
+    {
+        MappedReadGuard {
+            ref_borrow: Ref::map(guard.ref_borrow, visitor),
+            release: guard.release,
+        }
+    }
 }
 
 impl<'rw, T> Deref for ReadGuard<'rw, T> {
     type Target = T;
 
     fn deref(&self) -> &Self::Target {
-        &*self.ref_borrow
+        &self.ref_borrow
     }
 }
 
-impl<'rw, T> Drop for ReadGuard<'rw, T> {
+/// A guard of a current read/shared-locking on a [`RwLock`], projected into a
+/// component of protected data through [`ReadGuard::map`].
+#[derive(Debug)]
+pub struct MappedReadGuard<'rw, T>
+where
+    T: ?Sized,
+{
+    ref_borrow: Ref<'rw, T>,
+    release: ReadRelease<'rw>,
+}
+
+impl<'rw, T> MappedReadGuard<'rw, T>
+where
+    T: ?Sized,
+{
+    /// Projects the guard further into a component of the projected data.
+    pub fn map<U, F>(guard: Self, visitor: F) -> MappedReadGuard<'rw, U>
+    where
+        U: ?Sized,
+        F: FnOnce(&T) -> &U,
+    {
+        MappedReadGuard {
+            ref_borrow: Ref::map(guard.ref_borrow, visitor),
+            release: guard.release,
+        }
+    }
+}
+
+impl<'rw, T> Deref for MappedReadGuard<'rw, T>
+where
+    T: ?Sized,
+{
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.ref_borrow
+    }
+}
+
+/// A guard of a current upgradeable read-locking on a [`RwLock`], created by
+/// [`RwLock::upgradeable_read`]. Can be derreferenced to get read access to
+/// protected data, and upgraded into a [`WriteGuard`].
+#[derive(Debug)]
+pub struct UpgradeableReadGuard<'rw, T> {
+    rw_lock: &'rw RwLock<T>,
+    ref_borrow: Ref<'rw, T>,
+    release: ReadRelease<'rw>,
+}
+
+impl<'rw, T> UpgradeableReadGuard<'rw, T> {
+    /// Upgrades into a write-locking, waiting until other readers release the
+    /// lock. Meanwhile, new readers wait as well, and no other write-locking
+    /// can happen. This is an associated function, called as
+    /// `UpgradeableReadGuard::upgrade(...)`, so it does not conflict with
+    /// methods of the protected data.
+    pub async fn upgrade(guard: Self) -> WriteGuard<'rw, T> {
+        Upgrade { guard: Some(guard), requested: false }.await
+    }
+
+    /// Tries to upgrade into a write-locking without blocking. If other
+    /// readers hold the lock, the guard is given back as an error.
+    pub fn try_upgrade(guard: Self) -> Result<WriteGuard<'rw, T>, Self> {
+        let token = guard.release.token;
+        if guard.rw_lock.with_queue(|queue| queue.try_upgrade(token)) {
+            Ok(guard.into_write())
+        } else {
+            Err(guard)
+        }
+    }
+
+    /// Turns into a normal read-locking, allowing another upgradeable
+    /// read-locking to happen.
+    pub fn downgrade(guard: Self) -> ReadGuard<'rw, T> {
+        guard.rw_lock.with_queue(Queue::downgrade_upgradeable_read);
+        ReadGuard { ref_borrow: guard.ref_borrow, release: guard.release }
+    }
+
+    fn into_write(self) -> WriteGuard<'rw, T> {
+        let Self { rw_lock, ref_borrow, release } = self;
+        drop(ref_borrow);
+        // The locking is handed over to the write guard, not released.
+        mem::forget(release);
+        rw_lock.do_write()
+    }
+}
+
+impl<'rw, T> Deref for UpgradeableReadGuard<'rw, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.ref_borrow
+    }
+}
+
+#[derive(Debug)]
+struct Upgrade<'rw, T> {
+    guard: Option<UpgradeableReadGuard<'rw, T>>,
+    requested: bool,
+}
+
+impl<'rw, T> Future for Upgrade<'rw, T> {
+    type Output = WriteGuard<'rw, T>;
+
+    fn poll(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Self::Output> {
+        let this = &mut *self;
+        let guard =
+            this.guard.as_ref().expect("upgrade polled after completion");
+        let token = guard.release.token;
+        let upgraded = guard.rw_lock.with_queue(|queue| {
+            if queue.write_owner == Some(token) {
+                true
+            } else if this.requested {
+                queue.upgrade_on_hold = Some(cx.waker().clone());
+                false
+            } else {
+                this.requested = true;
+                queue.request_upgrade(cx.waker().clone());
+                queue.write_owner == Some(token)
+            }
+        });
+        match this.guard.take() {
+            Some(guard) if upgraded => Poll::Ready(guard.into_write()),
+            guard => {
+                this.guard = guard;
+                Poll::Pending
+            },
+        }
+    }
+}
+
+impl<'rw, T> Drop for Upgrade<'rw, T> {
     fn drop(&mut self) {
-        self.rw_lock.with_queue(|queue| queue.release_read(self.token));
+        if let Some(guard) = self.guard.take() {
+            if self.requested {
+                let UpgradeableReadGuard { rw_lock, ref_borrow, release } =
+                    guard;
+                let token = release.token;
+                drop(ref_borrow);
+                // The locking is released by cancelling the upgrade.
+                mem::forget(release);
+                rw_lock.with_queue(|queue| queue.cancel_upgrade(token));
+            }
+        }
     }
 }
 
@@ -295,25 +739,187 @@ impl<'rw, T> Drop for ReadGuard<'rw, T> {
 pub struct WriteGuard<'rw, T> {
     rw_lock: &'rw RwLock<T>,
     ref_mut: RefMut<'rw, T>,
+    release: WriteRelease<'rw>,
+}
+
+impl<'rw, T> WriteGuard<'rw, T> {
+    /// Projects the guard into a component of the protected data, such as a
+    /// field. The lock remains write-locked until the returned guard is
+    /// dropped. This is an associated function, called as
+    /// `WriteGuard::map(...)`, so it does not conflict with methods of the
+    /// protected data.
+    pub fn map<U, F>(guard: Self, visitor: F) -> MappedWriteGuard<'rw, U>
+    where
+        U: ?Sized,
+        F: FnOnce(&mut T) -> &mut U,
+    {
+        MappedWriteGuard {
+            ref_mut: RefMut::map(guard.ref_mut, visitor),
+            release: guard.release,
+        }
+    }
+
+    /// Atomically turns the write-locking into a read-locking, i.e. no other
+    /// write-locking can happen in between. Readers waiting since before the
+    /// next waiting writer acquire the lock along with the returned guard.
+    pub fn downgrade(self) -> ReadGuard<'rw, T> {
+        let Self { rw_lock, ref_mut, release } = self;
+        drop(ref_mut);
+        // The locking is handed over to the read guard, not released.
+        mem::forget(release);
+        let token = rw_lock.with_queue(Queue::downgrade_write);
+        rw_lock.do_read(token)
+    }
 }
 
 impl<'rw, T> Deref for WriteGuard<'rw, T> {
     type Target = T;
 
     fn deref(&self) -> &Self::Target {
-        &*self.ref_mut
+        &self.ref_mut
     }
 }
 
 impl<'rw, T> DerefMut for WriteGuard<'rw, T> {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut *self.ref_mut
+        &mut self.ref_mut
+    }
+}
+
+/// A guard of a current write/exclusive-locking on a [`RwLock`], projected
+/// into a component of protected data through [`WriteGuard::map`].
+#[derive(Debug)]
+pub struct MappedWriteGuard<'rw, T>
+where
+    T: ?Sized,
+{
+    ref_mut: RefMut<'rw, T>,
+    release: WriteRelease<'rw>,
+}
+
+impl<'rw, T> MappedWriteGuard<'rw, T>
+where
+    T: ?Sized,
+{
+    /// Projects the guard further into a component of the projected data.
+    pub fn map<U, F>(guard: Self, visitor: F) -> MappedWriteGuard<'rw, U>
+    where
+        U: ?Sized,
+        F: FnOnce(&mut T) -> &mut U,
+    {
+        MappedWriteGuard {
+            ref_mut: RefMut::map(guard.ref_mut, visitor),
+            release: guard.release,
+        }
+    }
+}
+
+impl<'rw, T> Deref for MappedWriteGuard<'rw, T>
+where
+    T: ?Sized,
+{
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.ref_mut
+    }
+}
+
+impl<'rw, T> DerefMut for MappedWriteGuard<'rw, T>
+where
+    T: ?Sized,
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.ref_mut
+    }
+}
+
+/// An owned guard of a current read/shared-locking on a [`RwLock`], created
+/// by [`RwLock::read_owned`]. Can be derreferenced to get read access to
+/// protected data. It keeps the lock alive, and it is `'static` if the data
+/// is.
+pub struct OwnedReadGuard<T> {
+    rw_lock: Rc<RwLock<T>>,
+    token: Token,
+}
+
+impl<T> OwnedReadGuard<T> {
+    /// The lock this guard read-locks.
+    pub fn rw_lock(&self) -> &Rc<RwLock<T>> {
+        &self.rw_lock
+    }
+}
+
+impl<T> Deref for OwnedReadGuard<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        // SAFETY: while this guard is alive, the queue grants no write guard,
+        // and the data is never accessed other than through guards.
+        unsafe { &*self.rw_lock.data.as_ptr() }
+    }
+}
+
+impl<T> fmt::Debug for OwnedReadGuard<T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        fmtr.debug_struct("OwnedReadGuard").field("data", &**self).finish()
+    }
+}
+
+impl<T> Drop for OwnedReadGuard<T> {
+    fn drop(&mut self) {
+        self.rw_lock.with_queue(|queue| queue.release_read(self.token));
+    }
+}
+
+/// An owned guard of a current write/exclusive-locking on a [`RwLock`],
+/// created by [`RwLock::write_owned`]. Can be derreferenced to get both read
+/// and write access to protected data. It keeps the lock alive, and it is
+/// `'static` if the data is.
+pub struct OwnedWriteGuard<T> {
+    rw_lock: Rc<RwLock<T>>,
+}
+
+impl<T> OwnedWriteGuard<T> {
+    /// The lock this guard write-locks.
+    pub fn rw_lock(&self) -> &Rc<RwLock<T>> {
+        &self.rw_lock
+    }
+}
+
+impl<T> Deref for OwnedWriteGuard<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        // SAFETY: while this guard is alive, the queue grants no other guard,
+        // and the data is never accessed other than through guards.
+        unsafe { &*self.rw_lock.data.as_ptr() }
+    }
+}
+
+impl<T> DerefMut for OwnedWriteGuard<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        // SAFETY: while this guard is alive, the queue grants no other guard,
+        // and the data is never accessed other than through guards.
+        unsafe { &mut *self.rw_lock.data.as_ptr() }
     }
 }
 
-impl<'rw, T> Drop for WriteGuard<'rw, T> {
+impl<T> fmt::Debug for OwnedWriteGuard<T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        fmtr.debug_struct("OwnedWriteGuard").field("data", &**self).finish()
+    }
+}
+
+impl<T> Drop for OwnedWriteGuard<T> {
     fn drop(&mut self) {
-        self.rw_lock.with_queue(|queue| queue.release_write());
+        self.rw_lock.with_queue(Queue::release_write);
     }
 }
 
@@ -327,6 +933,7 @@ enum ReadSubscriberState {
 #[derive(Debug)]
 struct ReadSubscriber<'rw, T> {
     rw_lock: &'rw RwLock<T>,
+    upgradeable: bool,
     state: ReadSubscriberState,
 }
 
@@ -352,7 +959,14 @@ impl<'rw, T> Future for ReadSubscriber<'rw, T> {
             ReadSubscriberState::NotSubscribed => {
                 self.rw_lock.with_queue(|queue| {
                     let token = queue.new_token();
-                    queue.acquire_read(cx.waker().clone(), token);
+                    if self.upgradeable {
+                        queue.acquire_upgradeable_read(
+                            cx.waker().clone(),
+                            token,
+                        );
+                    } else {
+                        queue.acquire_read(cx.waker().clone(), token);
+                    }
                     self.state = ReadSubscriberState::Subscribed(token);
                     Poll::Pending
                 })
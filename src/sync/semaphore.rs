@@ -0,0 +1,330 @@
+use std::{
+    cell::Cell,
+    collections::BTreeMap,
+    error::Error,
+    fmt,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll, Waker},
+};
+
+type Token = usize;
+
+#[derive(Debug, Clone)]
+struct Request {
+    permits: usize,
+    waker: Waker,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Queue {
+    permits: usize,
+    closed: bool,
+    granted: BTreeMap<Token, usize>,
+    on_hold: BTreeMap<Token, Request>,
+}
+
+impl Queue {
+    fn new(permits: usize) -> Self {
+        Self { permits, ..Self::default() }
+    }
+
+    fn new_token(&self) -> Token {
+        let max_granted = self.granted.keys().next_back().copied();
+        let max_on_hold = self.on_hold.keys().next_back().copied();
+        max_granted.max(max_on_hold).map_or(0, |token| token + 1)
+    }
+
+    fn acquire(&mut self, waker: Waker, token: Token, permits: usize) {
+        if self.closed {
+            waker.wake();
+        } else if self.on_hold.is_empty() && self.permits >= permits {
+            self.permits -= permits;
+            self.granted.insert(token, permits);
+            waker.wake();
+        } else {
+            self.on_hold.insert(token, Request { permits, waker });
+        }
+    }
+
+    fn try_acquire(&mut self, permits: usize) -> Result<(), TryAcquireError> {
+        if self.closed {
+            Err(TryAcquireError::Closed)
+        } else if self.on_hold.is_empty() && self.permits >= permits {
+            self.permits -= permits;
+            Ok(())
+        } else {
+            Err(TryAcquireError::NoPermits)
+        }
+    }
+
+    fn release(&mut self, permits: usize) {
+        self.permits = self
+            .permits
+            .checked_add(permits)
+            .expect("semaphore permit count overflow");
+        self.forward();
+    }
+
+    fn close(&mut self) {
+        self.closed = true;
+        while let Some((_, request)) = self.on_hold.pop_first() {
+            request.waker.wake();
+        }
+    }
+
+    fn cancel(&mut self, token: Token) {
+        if let Some(permits) = self.granted.remove(&token) {
+            self.release(permits);
+        } else if self.on_hold.remove(&token).is_some() {
+            self.forward();
+        }
+    }
+
+    fn forward(&mut self) {
+        while let Some(entry) = self.on_hold.first_entry() {
+            if entry.get().permits > self.permits {
+                break;
+            }
+            let (token, request) = entry.remove_entry();
+            self.permits -= request.permits;
+            self.granted.insert(token, request.permits);
+            request.waker.wake();
+        }
+    }
+}
+
+/// The error returned by [`Semaphore::acquire`] when the semaphore is closed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AcquireError;
+
+impl fmt::Display for AcquireError {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmtr, "semaphore closed")
+    }
+}
+
+impl Error for AcquireError {}
+
+/// The error returned by [`Semaphore::try_acquire`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TryAcquireError {
+    /// The semaphore is closed.
+    Closed,
+    /// There are not enough permits available, or other tasks are already
+    /// waiting for permits.
+    NoPermits,
+}
+
+impl fmt::Display for TryAcquireError {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Closed => write!(fmtr, "semaphore closed"),
+            Self::NoPermits => write!(fmtr, "no permits available"),
+        }
+    }
+}
+
+impl Error for TryAcquireError {}
+
+/// Counting semaphore limiting how many critical sections run concurrently.
+/// Behaves much like [`tokio::sync::Semaphore`], but designed for WASM
+/// (single-thread, thus this struct is Unsync). This semaphore is fair: permits
+/// are handed out in the order they were requested, and a request for many
+/// permits holds back the requests made after it.
+pub struct Semaphore {
+    queue: Cell<Queue>,
+}
+
+impl Semaphore {
+    fn with_queue<F, A>(&self, visitor: F) -> A
+    where
+        F: FnOnce(&mut Queue) -> A,
+    {
+        let mut queue = self.queue.take();
+        let output = visitor(&mut queue);
+        self.queue.set(queue);
+        output
+    }
+
+    /// Creates a semaphore with the given number of initial permits.
+    pub fn new(permits: usize) -> Self {
+        Self { queue: Cell::new(Queue::new(permits)) }
+    }
+
+    /// Returns the number of permits currently available.
+    pub fn available_permits(&self) -> usize {
+        self.with_queue(|queue| queue.permits)
+    }
+
+    /// Adds permits to the semaphore, handing them out to waiting tasks if
+    /// possible.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of available permits overflows.
+    pub fn add_permits(&self, permits: usize) {
+        self.with_queue(|queue| queue.release(permits));
+    }
+
+    /// Closes the semaphore. Tasks waiting for permits, as well as further
+    /// attempts to acquire permits, fail. Permits already acquired are not
+    /// affected.
+    pub fn close(&self) {
+        self.with_queue(Queue::close);
+    }
+
+    /// Returns whether the semaphore is closed.
+    pub fn is_closed(&self) -> bool {
+        self.with_queue(|queue| queue.closed)
+    }
+
+    /// Tries to acquire the given number of permits without blocking. Fails if
+    /// there are not enough permits available, if other tasks are already
+    /// waiting for permits, or if the semaphore is closed. While the returned
+    /// permit is not dropped, the permits remain acquired.
+    pub fn try_acquire(
+        &self,
+        permits: usize,
+    ) -> Result<SemaphorePermit<'_>, TryAcquireError> {
+        self.with_queue(|queue| queue.try_acquire(permits))?;
+        Ok(SemaphorePermit { semaphore: self, permits })
+    }
+
+    /// Acquires the given number of permits, waiting until they are available.
+    /// Fails if the semaphore is closed. While the returned permit is not
+    /// dropped, the permits remain acquired.
+    ///
+    /// ```no_run
+    /// use std::rc::Rc;
+    /// use webio::{sync::Semaphore, task};
+    ///
+    /// # fn main() {
+    /// # task::detach(async {
+    /// let fetches = Rc::new(Semaphore::new(2));
+    /// let mut handles = Vec::new();
+    /// for url in ["/a", "/b", "/c"] {
+    ///     let fetches = fetches.clone();
+    ///     handles.push(task::spawn(async move {
+    ///         let _permit = fetches.acquire(1).await.unwrap();
+    ///         // At most two fetches at the same time.
+    ///         # let _ = url;
+    ///     }));
+    /// }
+    /// for handle in handles {
+    ///     handle.await.unwrap();
+    /// }
+    /// # });
+    /// # }
+    /// ```
+    pub async fn acquire(
+        &self,
+        permits: usize,
+    ) -> Result<SemaphorePermit<'_>, AcquireError> {
+        let subscriber = Subscriber {
+            semaphore: self,
+            permits,
+            state: SubscriberState::NotSubscribed,
+        };
+        subscriber.await?;
+        Ok(SemaphorePermit { semaphore: self, permits })
+    }
+}
+
+impl fmt::Debug for Semaphore {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        self.with_queue(|queue| {
+            fmtr.debug_struct("Semaphore").field("queue", &queue).finish()
+        })
+    }
+}
+
+/// Permits acquired from a [`Semaphore`]. The permits are given back to the
+/// semaphore when this is dropped.
+#[derive(Debug)]
+pub struct SemaphorePermit<'semaphore> {
+    semaphore: &'semaphore Semaphore,
+    permits: usize,
+}
+
+impl<'semaphore> SemaphorePermit<'semaphore> {
+    /// Returns the number of permits held.
+    pub fn permits(&self) -> usize {
+        self.permits
+    }
+
+    /// Forgets the permits without giving them back to the semaphore, reducing
+    /// the number of permits the semaphore hands out.
+    pub fn forget(mut self) {
+        self.permits = 0;
+    }
+}
+
+impl<'semaphore> Drop for SemaphorePermit<'semaphore> {
+    fn drop(&mut self) {
+        if self.permits > 0 {
+            self.semaphore.with_queue(|queue| queue.release(self.permits));
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum SubscriberState {
+    NotSubscribed,
+    Subscribed(Token),
+    Done,
+}
+
+#[derive(Debug)]
+struct Subscriber<'semaphore> {
+    semaphore: &'semaphore Semaphore,
+    permits: usize,
+    state: SubscriberState,
+}
+
+impl<'semaphore> Future for Subscriber<'semaphore> {
+    type Output = Result<(), AcquireError>;
+
+    fn poll(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Self::Output> {
+        match self.state {
+            SubscriberState::Done => Poll::Ready(Ok(())),
+            SubscriberState::Subscribed(token) => {
+                self.semaphore.with_queue(|queue| {
+                    if queue.granted.remove(&token).is_some() {
+                        self.state = SubscriberState::Done;
+                        Poll::Ready(Ok(()))
+                    } else if queue.closed {
+                        self.state = SubscriberState::Done;
+                        Poll::Ready(Err(AcquireError))
+                    } else {
+                        if let Some(request) = queue.on_hold.get_mut(&token) {
+                            request.waker.clone_from(cx.waker());
+                        }
+                        Poll::Pending
+                    }
+                })
+            },
+            SubscriberState::NotSubscribed => {
+                self.semaphore.with_queue(|queue| {
+                    let token = queue.new_token();
+                    queue.acquire(cx.waker().clone(), token, self.permits);
+                    self.state = SubscriberState::Subscribed(token);
+                    Poll::Pending
+                })
+            },
+        }
+    }
+}
+
+impl<'semaphore> Drop for Subscriber<'semaphore> {
+    fn drop(&mut self) {
+        if let SubscriberState::Subscribed(token) = self.state {
+            self.semaphore.with_queue(|queue| {
+                queue.cancel(token);
+            })
+        }
+    }
+}
@@ -1,10 +1,80 @@
 //! This module exports items related to task spawning.
 
+mod join_set;
+mod local;
+mod builder;
+mod registry;
+#[cfg(feature = "time")]
+mod budget;
+#[cfg(feature = "time")]
+mod yielding;
+#[cfg(feature = "time")]
+mod idle;
+#[cfg(feature = "time")]
+mod scheduler;
+
 use crate::callback;
 use pin_project::pin_project;
-use std::{error::Error, fmt, future::Future, pin::Pin, task};
+use std::{
+    any::Any,
+    cell::Cell,
+    error::Error,
+    fmt,
+    future::Future,
+    panic::{self, AssertUnwindSafe, Location},
+    pin::Pin,
+    rc::Rc,
+    task,
+};
+
+#[cfg(target_arch = "wasm32")]
 use wasm_bindgen_futures::spawn_local;
 
+#[cfg(not(target_arch = "wasm32"))]
+use crate::native::spawn_local;
+
+#[cfg(feature = "time")]
+use budget::{budgeted, Budgeted};
+
+pub use builder::Builder;
+pub use join_set::JoinSet;
+pub use local::{AccessError, LocalKey, TaskLocalFuture};
+pub use registry::TaskId;
+
+#[cfg(debug_assertions)]
+#[cfg_attr(feature = "feature-doc-cfg", doc(cfg(debug_assertions)))]
+pub use registry::{tasks, TaskInfo, TaskState};
+
+#[cfg(all(debug_assertions, feature = "macros"))]
+#[cfg_attr(
+    feature = "feature-doc-cfg",
+    doc(cfg(all(debug_assertions, feature = "macros")))
+)]
+pub use registry::dump_tasks;
+
+#[cfg(feature = "time")]
+#[cfg_attr(feature = "feature-doc-cfg", doc(cfg(feature = "time")))]
+pub use budget::{
+    budget,
+    budget_remaining,
+    consume_budget,
+    maybe_yield,
+    set_budget,
+    DEFAULT_BUDGET,
+};
+
+#[cfg(feature = "time")]
+#[cfg_attr(feature = "feature-doc-cfg", doc(cfg(feature = "time")))]
+pub use yielding::{yield_with, YieldStrategy};
+
+#[cfg(feature = "time")]
+#[cfg_attr(feature = "feature-doc-cfg", doc(cfg(feature = "time")))]
+pub use idle::{idle_deadline, spawn_idle};
+
+#[cfg(feature = "time")]
+#[cfg_attr(feature = "feature-doc-cfg", doc(cfg(feature = "time")))]
+pub use scheduler::Priority;
+
 /// Spawns an asynchronous task in JS event loop.
 ///
 /// # Examples
@@ -22,15 +92,65 @@ use wasm_bindgen_futures::spawn_local;
 /// # });
 /// # }
 /// ```
+///
+/// ## Aborting Tasks
+/// ```no_run
+/// use webio::task;
+///
+/// # fn main() {
+/// # task::detach(async {
+/// let handle = task::spawn(std::future::pending::<()>());
+/// handle.abort();
+/// assert!(handle.await.unwrap_err().is_cancelled());
+/// # });
+/// # }
+/// ```
+#[track_caller]
 pub fn spawn<A>(future: A) -> JoinHandle<A::Output>
 where
     A: Future + 'static,
 {
+    spawn_with(future, Builder::new(), spawn_local)
+}
+
+type TaskFuture = Budgeted<
+    Abortable<
+        registry::Instrumented<callback::once::AsyncCbHandlerFuture<'static>>,
+    >,
+>;
+
+/// Without the `time` feature, there is no budget to track.
+#[cfg(not(feature = "time"))]
+type Budgeted<A> = A;
+
+#[cfg(not(feature = "time"))]
+fn budgeted<A>(future: A) -> Budgeted<A> {
+    future
+}
+
+#[track_caller]
+fn spawn_with<A, S>(
+    future: A,
+    builder: Builder,
+    spawner: S,
+) -> JoinHandle<A::Output>
+where
+    A: Future + 'static,
+    S: FnOnce(TaskFuture),
+{
+    let location = Location::caller();
+    let abort_handle = AbortHandle::new();
+    let id = registry::register(builder.name, location, abort_handle.clone());
     let register = callback::once::AsyncRegister::new(|callback| {
-        spawn_local(callback(()))
+        let instrumented = registry::instrument(id, callback(()));
+        spawner(budgeted(Abortable::new(
+            instrumented,
+            abort_handle.clone(),
+            builder.catch_panics,
+        )))
     });
     let callback_handle = register.listen(|()| future);
-    JoinHandle::new(callback_handle)
+    JoinHandle::new(callback_handle, abort_handle, id)
 }
 
 /// Detaches a future from the current WASM call, but ensures the future
@@ -39,11 +159,13 @@ pub fn detach<A>(future: A)
 where
     A: Future<Output = ()> + 'static,
 {
-    wasm_bindgen_futures::spawn_local(future);
+    spawn_local(budgeted(future));
 }
 
 /// Yields control back to the event loop once and returns back to execution as
-/// soon as possible.
+/// soon as possible. This goes through the microtask queue, and so the browser
+/// does not render nor handle input in the meantime; see `yield_with` (requires
+/// the `time` feature) for other strategies.
 ///
 /// # Example
 ///
@@ -62,38 +184,217 @@ where
 /// # }
 /// ```
 pub async fn yield_now() {
-    spawn(async {}).await.unwrap()
+    YieldNow { yielded: false }.await
+}
+
+/// Completes on its second poll, waking its task on the first one, so that the
+/// task is queued again behind the work already pending.
+struct YieldNow {
+    yielded: bool,
+}
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(
+        mut self: Pin<&mut Self>,
+        ctx: &mut task::Context<'_>,
+    ) -> task::Poll<Self::Output> {
+        if self.yielded {
+            return task::Poll::Ready(());
+        }
+        self.yielded = true;
+        ctx.waker().wake_by_ref();
+        task::Poll::Pending
+    }
 }
 
 /// An error that might happen when waiting for a task, typically caused because
 /// the task was cancelled.
 #[derive(Debug)]
 pub struct JoinError {
-    cause: callback::Cancelled,
+    kind: JoinErrorKind,
+}
+
+#[derive(Debug)]
+enum JoinErrorKind {
+    Cancelled(callback::Cancelled),
+    Panicked(String),
+}
+
+impl JoinError {
+    /// Returns whether the task was cancelled, i.e. whether its future was
+    /// dropped before completing, such as when the task is aborted.
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self.kind, JoinErrorKind::Cancelled(_))
+    }
+
+    /// Returns whether the task panicked. Only tasks spawned with
+    /// `Builder::catch_panics`, which requires `panic=unwind`, can fail this
+    /// way.
+    pub fn is_panic(&self) -> bool {
+        matches!(self.kind, JoinErrorKind::Panicked(_))
+    }
+
+    /// Returns the message of the panic payload, if the task panicked.
+    pub fn panic_message(&self) -> Option<&str> {
+        match &self.kind {
+            JoinErrorKind::Panicked(message) => Some(message),
+            _ => None,
+        }
+    }
 }
 
 impl fmt::Display for JoinError {
     fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
-        write!(fmtr, "{}", self.cause)
+        match &self.kind {
+            JoinErrorKind::Cancelled(cause) => write!(fmtr, "{}", cause),
+            JoinErrorKind::Panicked(message) => {
+                write!(fmtr, "task panicked: {}", message)
+            },
+        }
     }
 }
 
 impl Error for JoinError {
     fn cause(&self) -> Option<&dyn Error> {
-        Some(&self.cause)
+        match &self.kind {
+            JoinErrorKind::Cancelled(cause) => Some(cause),
+            JoinErrorKind::Panicked(_) => None,
+        }
+    }
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => match payload.downcast::<&'static str>() {
+            Ok(message) => String::from(*message),
+            Err(_) => String::from("Box<dyn Any>"),
+        },
+    }
+}
+
+#[derive(Default)]
+struct AbortState {
+    aborted: Cell<bool>,
+    waker: Cell<Option<task::Waker>>,
+    panic: Cell<Option<String>>,
+}
+
+/// A handle that allows the caller to abort a task without joining it. Can be
+/// cloned and passed around freely.
+#[derive(Clone)]
+pub struct AbortHandle {
+    state: Rc<AbortState>,
+}
+
+impl fmt::Debug for AbortHandle {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        fmtr.debug_struct("AbortHandle")
+            .field("aborted", &self.state.aborted)
+            .finish()
+    }
+}
+
+impl AbortHandle {
+    fn new() -> Self {
+        Self { state: Rc::new(AbortState::default()) }
+    }
+
+    /// Aborts the task. The task's future is dropped at its next suspension
+    /// point, and joining the task yields a cancellation error, unless the
+    /// task already completed. Aborting more than once has no further effect.
+    pub fn abort(&self) {
+        self.state.aborted.set(true);
+        if let Some(waker) = self.state.waker.take() {
+            waker.wake();
+        }
+    }
+
+    /// Returns whether the task was requested to abort.
+    pub fn is_aborted(&self) -> bool {
+        self.state.aborted.get()
+    }
+}
+
+#[pin_project]
+struct Abortable<A> {
+    #[pin]
+    future: A,
+    handle: AbortHandle,
+    catch_panics: bool,
+}
+
+impl<A> Abortable<A> {
+    fn new(future: A, handle: AbortHandle, catch_panics: bool) -> Self {
+        Self { future, handle, catch_panics }
+    }
+}
+
+impl<A> Future for Abortable<A>
+where
+    A: Future<Output = ()>,
+{
+    type Output = ();
+
+    fn poll(
+        self: Pin<&mut Self>,
+        ctx: &mut task::Context<'_>,
+    ) -> task::Poll<Self::Output> {
+        let this = self.project();
+        if this.handle.is_aborted() {
+            return task::Poll::Ready(());
+        }
+        this.handle.state.waker.set(Some(ctx.waker().clone()));
+        if !*this.catch_panics {
+            return this.future.poll(ctx);
+        }
+        let future = this.future;
+        match panic::catch_unwind(AssertUnwindSafe(|| future.poll(ctx))) {
+            Ok(poll) => poll,
+            Err(payload) => {
+                this.handle.state.panic.set(Some(panic_message(payload)));
+                task::Poll::Ready(())
+            },
+        }
     }
 }
 
 /// A handle that allows the caller to join a task (i.e. wait for it to end).
+/// Dropping the handle detaches the task, rather than aborting it.
 #[pin_project]
 pub struct JoinHandle<T> {
     #[pin]
     inner: callback::once::Listener<T>,
+    abort_handle: AbortHandle,
+    id: TaskId,
 }
 
 impl<T> JoinHandle<T> {
-    fn new(inner: callback::once::Listener<T>) -> Self {
-        Self { inner }
+    fn new(
+        inner: callback::once::Listener<T>,
+        abort_handle: AbortHandle,
+        id: TaskId,
+    ) -> Self {
+        Self { inner, abort_handle, id }
+    }
+
+    /// Returns the identifier of the task.
+    pub fn id(&self) -> TaskId {
+        self.id
+    }
+
+    /// Aborts the task. The task's future is dropped at its next suspension
+    /// point, and joining the task yields a cancellation error, unless the
+    /// task already completed.
+    pub fn abort(&self) {
+        self.abort_handle.abort();
+    }
+
+    /// Creates a new handle that can abort this task, but not join it.
+    pub fn abort_handle(&self) -> AbortHandle {
+        self.abort_handle.clone()
     }
 }
 
@@ -104,9 +405,16 @@ impl<T> Future for JoinHandle<T> {
         self: Pin<&mut Self>,
         ctx: &mut task::Context<'_>,
     ) -> task::Poll<Self::Output> {
-        self.project()
-            .inner
-            .poll(ctx)
-            .map(|result| result.map_err(|cause| JoinError { cause }))
+        let this = self.project();
+        let abort_handle = &*this.abort_handle;
+        this.inner.poll(ctx).map(|result| {
+            result.map_err(|cause| {
+                let kind = match abort_handle.state.panic.take() {
+                    Some(message) => JoinErrorKind::Panicked(message),
+                    None => JoinErrorKind::Cancelled(cause),
+                };
+                JoinError { kind }
+            })
+        })
     }
 }
@@ -0,0 +1,160 @@
+//! Implementation of cooperative scheduling through a time budget.
+//!
+//! The budget is tracked per task: every time a task spawned by webio is
+//! resumed, i.e. polled after it yielded, its budget starts afresh. Time spent
+//! by other tasks does not count against it. Futures that do not run as a
+//! webio task, such as the root future of a test, get their budget counted
+//! from the first time they consume it since they last yielded through
+//! [`maybe_yield`].
+
+use crate::{
+    task::{self, YieldStrategy},
+    time,
+};
+use pin_project::pin_project;
+use std::{
+    cell::Cell,
+    future::Future,
+    pin::Pin,
+    task as std_task,
+    time::Duration,
+};
+
+/// Default frame budget used by [`consume_budget`]: about half of a frame in a
+/// 60 Hz display.
+pub const DEFAULT_BUDGET: Duration = Duration::from_millis(8);
+
+thread_local! {
+    static BUDGET: Cell<Duration> = const { Cell::new(DEFAULT_BUDGET) };
+    /// When the current run of the task being polled started.
+    static RUN_START: Cell<Option<time::Instant>> = const { Cell::new(None) };
+}
+
+/// Returns the current frame budget, i.e. for how long Rust code can run
+/// before [`consume_budget`] yields control back to the browser.
+pub fn budget() -> Duration {
+    BUDGET.with(Cell::get)
+}
+
+/// Sets the frame budget, i.e. for how long Rust code can run before
+/// [`consume_budget`] yields control back to the browser. Defaults to
+/// [`DEFAULT_BUDGET`].
+pub fn set_budget(budget: Duration) {
+    BUDGET.with(|cell| cell.set(budget));
+}
+
+/// Returns how much of the frame budget remains for the current task, i.e.
+/// since it was last resumed.
+pub fn budget_remaining() -> Duration {
+    budget().saturating_sub(run_elapsed())
+}
+
+fn run_elapsed() -> Duration {
+    let now = time::Instant::now();
+    RUN_START.with(|cell| match cell.get() {
+        Some(start) => now.saturating_duration_since(start),
+        None => {
+            cell.set(Some(now));
+            Duration::ZERO
+        },
+    })
+}
+
+/// Restores the run of the enclosing task when a task is done being polled.
+struct RunGuard {
+    enclosing: Option<time::Instant>,
+}
+
+impl Drop for RunGuard {
+    fn drop(&mut self) {
+        RUN_START.with(|cell| cell.set(self.enclosing));
+    }
+}
+
+/// A task's future, whose budget starts afresh whenever it is polled.
+#[pin_project]
+pub(super) struct Budgeted<A> {
+    #[pin]
+    future: A,
+}
+
+pub(super) fn budgeted<A>(future: A) -> Budgeted<A> {
+    Budgeted { future }
+}
+
+impl<A> Future for Budgeted<A>
+where
+    A: Future,
+{
+    type Output = A::Output;
+
+    fn poll(
+        self: Pin<&mut Self>,
+        ctx: &mut std_task::Context<'_>,
+    ) -> std_task::Poll<Self::Output> {
+        let start = time::Instant::now();
+        let enclosing = RUN_START.with(|cell| cell.replace(Some(start)));
+        let _guard = RunGuard { enclosing };
+        self.project().future.poll(ctx)
+    }
+}
+
+/// Consumes the cooperative budget of the current task. If the time elapsed
+/// since the task was last resumed exceeds the frame budget (see
+/// [`set_budget`]), control is yielded back to the browser, letting it render
+/// and handle input, otherwise this completes immediately.
+///
+/// Useful for CPU-bound loops, which can call this function at every step
+/// without freezing the browser. Time spent by other tasks does not count
+/// against the budget of the current task.
+///
+/// # Examples
+///
+/// ```no_run
+/// use webio::task;
+///
+/// # fn main() {
+/// # task::detach(async {
+/// let mut sum = 0u64;
+/// for i in 0 .. 100_000_000u64 {
+///     sum = sum.wrapping_add(i * i);
+///     task::consume_budget().await;
+/// }
+/// # });
+/// # }
+/// ```
+pub async fn consume_budget() {
+    maybe_yield().await;
+}
+
+/// Yields control back to the browser if the frame budget of the current task
+/// is exhausted, just like [`consume_budget`], but also returns whether control
+/// was yielded. Useful to do some work only right after the browser got control
+/// back, e.g. reporting progress.
+///
+/// # Examples
+///
+/// ```no_run
+/// use webio::task;
+///
+/// # fn main() {
+/// # task::detach(async {
+/// let mut sum = 0u64;
+/// for i in 0 .. 100_000_000u64 {
+///     sum = sum.wrapping_add(i * i);
+///     if task::maybe_yield().await {
+///         println!("progress: {}", i);
+///     }
+/// }
+/// # });
+/// # }
+/// ```
+pub async fn maybe_yield() -> bool {
+    if run_elapsed() >= budget() {
+        task::yield_with(YieldStrategy::Macrotask).await;
+        RUN_START.with(|cell| cell.set(None));
+        true
+    } else {
+        false
+    }
+}
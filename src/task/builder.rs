@@ -0,0 +1,94 @@
+//! Implementation of a builder for configuring tasks before spawning them.
+
+use super::{spawn_local, spawn_with, JoinHandle};
+use std::future::Future;
+
+#[cfg(feature = "time")]
+use super::{scheduler, Priority};
+
+/// A builder of tasks, allowing tasks to be configured before they are
+/// spawned. A task spawned by a builder with no configuration behaves exactly
+/// like a task spawned by [`spawn`](super::spawn).
+///
+/// # Examples
+/// ```no_run
+/// use webio::task::{self, Builder, Priority};
+///
+/// # fn main() {
+/// # task::detach(async {
+/// let handle = Builder::new()
+///     .name("addition")
+///     .priority(Priority::Background)
+///     .spawn(async { 2 + 2 });
+/// assert_eq!(handle.await.unwrap(), 4);
+/// # });
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Builder {
+    pub(super) name: Option<String>,
+    pub(super) catch_panics: bool,
+    #[cfg(feature = "time")]
+    priority: Option<Priority>,
+}
+
+impl Builder {
+    /// Creates a builder with no configuration.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the name of the task, shown when listing live tasks through
+    /// `tasks`, which is only available in debug builds.
+    pub fn name<S>(mut self, name: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Sets whether panics of the task's future are caught. If caught, a panic
+    /// only fails this task, whose [`JoinHandle`] then resolves to an error
+    /// carrying the panic message (see [`JoinError::panic_message`]), while
+    /// other tasks keep running. Disabled by default.
+    ///
+    /// Only available when built with `panic=unwind`, since panics are caught
+    /// by unwinding. WASM targets abort on panic by default, and an aborting
+    /// panic cannot be confined to a single task.
+    ///
+    /// [`JoinError::panic_message`]: super::JoinError::panic_message
+    #[cfg(panic = "unwind")]
+    #[cfg_attr(feature = "feature-doc-cfg", doc(cfg(panic = "unwind")))]
+    pub fn catch_panics(mut self, enabled: bool) -> Self {
+        self.catch_panics = enabled;
+        self
+    }
+
+    /// Sets the priority of the task. Tasks with a priority are run by a
+    /// webio-owned executor, mapped onto `scheduler.postTask` when the
+    /// Prioritized Task Scheduling API is available, and emulated otherwise.
+    #[cfg(feature = "time")]
+    #[cfg_attr(feature = "feature-doc-cfg", doc(cfg(feature = "time")))]
+    pub fn priority(mut self, priority: Priority) -> Self {
+        self.priority = Some(priority);
+        self
+    }
+
+    /// Spawns an asynchronous task in JS event loop using this builder's
+    /// configuration.
+    #[track_caller]
+    pub fn spawn<A>(self, future: A) -> JoinHandle<A::Output>
+    where
+        A: Future + 'static,
+    {
+        #[cfg(feature = "time")]
+        if let Some(priority) = self.priority {
+            return spawn_with(future, self, |task_future| {
+                scheduler::spawn(priority, task_future)
+            });
+        }
+
+        spawn_with(future, self, spawn_local)
+    }
+}
@@ -0,0 +1,82 @@
+//! Implementation of tasks that only make progress while the browser is idle.
+
+use super::{spawn, JoinHandle};
+use crate::time::{self, IdleDeadline, IdleHandle};
+use pin_project::pin_project;
+use std::{future::Future, pin::Pin, task};
+
+crate::task_local! {
+    static DEADLINE: IdleDeadline;
+}
+
+/// Spawns an asynchronous task in JS event loop that only makes progress while
+/// the browser is idle, i.e. the task's future is only polled during idle
+/// periods (see [`time::idle`]). The deadline of the current idle period is
+/// available to the task through [`idle_deadline`].
+///
+/// # Examples
+/// ```no_run
+/// use std::time::Duration;
+/// use webio::task;
+///
+/// # fn main() {
+/// # task::detach(async {
+/// # fn index_next() -> bool { false }
+/// let handle = task::spawn_idle(async {
+///     let mut indexed = 0;
+///     while index_next() {
+///         indexed += 1;
+///         let remaining = task::idle_deadline()
+///             .map_or(Duration::ZERO, |deadline| deadline.time_remaining());
+///         if remaining < Duration::from_millis(1) {
+///             task::yield_now().await;
+///         }
+///     }
+///     indexed
+/// });
+/// let indexed = handle.await.unwrap();
+/// # });
+/// # }
+/// ```
+#[track_caller]
+pub fn spawn_idle<A>(future: A) -> JoinHandle<A::Output>
+where
+    A: Future + 'static,
+{
+    spawn(Idle { future, request: None })
+}
+
+/// Returns the deadline of the current idle period, if the current task was
+/// spawned through [`spawn_idle`].
+pub fn idle_deadline() -> Option<IdleDeadline> {
+    DEADLINE.try_with(|deadline| *deadline).ok()
+}
+
+#[pin_project]
+struct Idle<A> {
+    #[pin]
+    future: A,
+    request: Option<IdleHandle>,
+}
+
+impl<A> Future for Idle<A>
+where
+    A: Future,
+{
+    type Output = A::Output;
+
+    fn poll(
+        self: Pin<&mut Self>,
+        ctx: &mut task::Context<'_>,
+    ) -> task::Poll<Self::Output> {
+        let this = self.project();
+        let request = this.request.get_or_insert_with(time::idle);
+        let deadline = match Pin::new(request).poll(ctx) {
+            task::Poll::Ready(deadline) => deadline,
+            task::Poll::Pending => return task::Poll::Pending,
+        };
+        *this.request = None;
+        let future = this.future;
+        DEADLINE.sync_scope(deadline, || future.poll(ctx))
+    }
+}
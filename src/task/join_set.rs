@@ -0,0 +1,124 @@
+//! Implementation of a dynamic group of spawned tasks.
+
+use super::{spawn, AbortHandle, JoinError, JoinHandle};
+use std::{fmt, future::Future, pin::Pin, task};
+
+/// A collection of tasks spawned through [`JoinSet::spawn`], where tasks can be
+/// pushed at runtime and joined in the order they complete. When the set is
+/// dropped, all tasks still in the set are aborted.
+///
+/// # Examples
+///
+/// ```no_run
+/// use webio::task::{self, JoinSet};
+///
+/// # fn main() {
+/// # task::detach(async {
+/// let mut set = JoinSet::new();
+/// for i in 0 .. 10u32 {
+///     set.spawn(async move { i * 2 });
+/// }
+/// assert_eq!(set.len(), 10);
+///
+/// let mut sum = 0;
+/// while let Some(result) = set.join_next().await {
+///     sum += result.unwrap();
+/// }
+/// assert_eq!(sum, 90);
+/// assert!(set.is_empty());
+/// # });
+/// # }
+/// ```
+pub struct JoinSet<T> {
+    handles: Vec<JoinHandle<T>>,
+}
+
+impl<T> JoinSet<T> {
+    /// Creates a new empty set of tasks.
+    pub fn new() -> Self {
+        Self { handles: Vec::new() }
+    }
+
+    /// Returns the number of tasks in the set that were not joined yet,
+    /// including tasks that already completed.
+    pub fn len(&self) -> usize {
+        self.handles.len()
+    }
+
+    /// Returns whether there are no tasks in the set.
+    pub fn is_empty(&self) -> bool {
+        self.handles.is_empty()
+    }
+
+    /// Spawns a task with [`spawn`] and pushes it into the set, returning a
+    /// handle that can abort the task.
+    #[track_caller]
+    pub fn spawn<A>(&mut self, future: A) -> AbortHandle
+    where
+        A: Future<Output = T> + 'static,
+        T: 'static,
+    {
+        let handle = spawn(future);
+        let abort_handle = handle.abort_handle();
+        self.handles.push(handle);
+        abort_handle
+    }
+
+    /// Waits for the next task in the set to complete and returns its output.
+    /// Returns `None` if the set is empty.
+    pub async fn join_next(&mut self) -> Option<Result<T, JoinError>> {
+        std::future::poll_fn(|ctx| self.poll_join_next(ctx)).await
+    }
+
+    /// Polls for the next task in the set to complete, returning its output
+    /// when ready, and `None` if the set is empty.
+    pub fn poll_join_next(
+        &mut self,
+        ctx: &mut task::Context<'_>,
+    ) -> task::Poll<Option<Result<T, JoinError>>> {
+        if self.handles.is_empty() {
+            return task::Poll::Ready(None);
+        }
+        for index in 0 .. self.handles.len() {
+            if let task::Poll::Ready(output) =
+                Pin::new(&mut self.handles[index]).poll(ctx)
+            {
+                self.handles.swap_remove(index);
+                return task::Poll::Ready(Some(output));
+            }
+        }
+        task::Poll::Pending
+    }
+
+    /// Aborts all tasks in the set. The tasks remain in the set, and joining
+    /// them yields cancellation errors, unless they already completed.
+    pub fn abort_all(&mut self) {
+        for handle in &self.handles {
+            handle.abort();
+        }
+    }
+
+    /// Removes all tasks from the set without aborting them, i.e. the tasks
+    /// keep running detached.
+    pub fn detach_all(&mut self) {
+        self.handles.clear();
+    }
+}
+
+impl<T> Default for JoinSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> fmt::Debug for JoinSet<T> {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        fmtr.debug_struct("JoinSet").field("len", &self.len()).finish()
+    }
+}
+
+impl<T> Drop for JoinSet<T> {
+    fn drop(&mut self) {
+        self.abort_all();
+    }
+}
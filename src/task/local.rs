@@ -0,0 +1,223 @@
+//! Implementation of task-local storage.
+
+use pin_project::{pin_project, pinned_drop};
+use std::{
+    cell::RefCell,
+    error::Error,
+    fmt,
+    future::Future,
+    mem,
+    pin::Pin,
+    task,
+    thread,
+};
+
+/// A key for task-local data, declared with [`task_local!`](crate::task_local).
+/// Values are set for the duration of a future through [`LocalKey::scope`],
+/// flowing through `.await` points of such future, while isolated from other
+/// tasks, even if their execution interleave in the JS event loop.
+pub struct LocalKey<T>
+where
+    T: 'static,
+{
+    #[doc(hidden)]
+    pub inner: thread::LocalKey<RefCell<Option<T>>>,
+}
+
+impl<T> LocalKey<T>
+where
+    T: 'static,
+{
+    /// Sets the value of this key for the duration of the given future. The
+    /// value is visible only while the returned future is being polled.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// webio::task_local! {
+    ///     static REQUEST_ID: u32;
+    /// }
+    ///
+    /// # fn main() {
+    /// # webio::task::detach(async {
+    /// REQUEST_ID
+    ///     .scope(42, async {
+    ///         webio::task::yield_now().await;
+    ///         assert_eq!(REQUEST_ID.get(), 42);
+    ///     })
+    ///     .await;
+    /// # });
+    /// # }
+    /// ```
+    pub fn scope<F>(&'static self, value: T, future: F) -> TaskLocalFuture<T, F>
+    where
+        F: Future,
+    {
+        TaskLocalFuture { local: self, slot: Some(value), future: Some(future) }
+    }
+
+    /// Sets the value of this key for the duration of the given synchronous
+    /// function.
+    pub fn sync_scope<F, R>(&'static self, value: T, function: F) -> R
+    where
+        F: FnOnce() -> R,
+    {
+        let mut slot = Some(value);
+        self.scope_inner(&mut slot, function)
+    }
+
+    fn scope_inner<F, R>(&'static self, slot: &mut Option<T>, function: F) -> R
+    where
+        F: FnOnce() -> R,
+    {
+        struct Guard<'slot, T>
+        where
+            T: 'static,
+        {
+            local: &'static LocalKey<T>,
+            slot: &'slot mut Option<T>,
+        }
+
+        impl<'slot, T> Drop for Guard<'slot, T>
+        where
+            T: 'static,
+        {
+            fn drop(&mut self) {
+                self.local.swap(self.slot);
+            }
+        }
+
+        self.swap(slot);
+        let _guard = Guard { local: self, slot };
+        function()
+    }
+
+    fn swap(&'static self, slot: &mut Option<T>) {
+        self.inner.with(|cell| mem::swap(slot, &mut *cell.borrow_mut()));
+    }
+
+    /// Accesses the current value of this key through the given function.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no value is set for this key in the current scope.
+    pub fn with<F, R>(&'static self, function: F) -> R
+    where
+        F: FnOnce(&T) -> R,
+    {
+        match self.try_with(function) {
+            Ok(output) => output,
+            Err(error) => panic!("{}", error),
+        }
+    }
+
+    /// Accesses the current value of this key through the given function. If
+    /// no value is set for this key in the current scope, an error is
+    /// returned.
+    pub fn try_with<F, R>(&'static self, function: F) -> Result<R, AccessError>
+    where
+        F: FnOnce(&T) -> R,
+    {
+        self.inner.with(|cell| match &*cell.borrow() {
+            Some(value) => Ok(function(value)),
+            None => Err(AccessError),
+        })
+    }
+
+    /// Returns a copy of the current value of this key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no value is set for this key in the current scope.
+    pub fn get(&'static self) -> T
+    where
+        T: Clone,
+    {
+        self.with(T::clone)
+    }
+}
+
+impl<T> fmt::Debug for LocalKey<T>
+where
+    T: 'static,
+{
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        fmtr.pad("LocalKey { .. }")
+    }
+}
+
+/// An error returned when accessing a task-local key outside of a scope where
+/// the key's value is set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AccessError;
+
+impl fmt::Display for AccessError {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmtr, "task-local value not set in the current scope")
+    }
+}
+
+impl Error for AccessError {}
+
+/// A future that sets a value of a task-local key while it is polled. Created
+/// by [`LocalKey::scope`].
+#[pin_project(PinnedDrop)]
+pub struct TaskLocalFuture<T, F>
+where
+    T: 'static,
+{
+    local: &'static LocalKey<T>,
+    slot: Option<T>,
+    #[pin]
+    future: Option<F>,
+}
+
+impl<T, F> Future for TaskLocalFuture<T, F>
+where
+    T: 'static,
+    F: Future,
+{
+    type Output = F::Output;
+
+    fn poll(
+        self: Pin<&mut Self>,
+        ctx: &mut task::Context<'_>,
+    ) -> task::Poll<Self::Output> {
+        let this = self.project();
+        let mut future = this.future;
+        this.local.scope_inner(this.slot, || {
+            let poll = future
+                .as_mut()
+                .as_pin_mut()
+                .expect("TaskLocalFuture polled after completion")
+                .poll(ctx);
+            if poll.is_ready() {
+                future.set(None);
+            }
+            poll
+        })
+    }
+}
+
+#[pinned_drop]
+impl<T, F> PinnedDrop for TaskLocalFuture<T, F>
+where
+    T: 'static,
+{
+    fn drop(self: Pin<&mut Self>) {
+        let this = self.project();
+        let mut future = this.future;
+        if future.is_some() {
+            this.local.scope_inner(this.slot, || future.set(None));
+        }
+    }
+}
+
+impl<T, F> fmt::Debug for TaskLocalFuture<T, F>
+where
+    T: fmt::Debug + 'static,
+{
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        fmtr.debug_struct("TaskLocalFuture").field("slot", &self.slot).finish()
+    }
+}
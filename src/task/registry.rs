@@ -0,0 +1,322 @@
+//! Implementation of the registry of live tasks, for introspection. Tasks are
+//! only recorded and instrumented in debug builds, so that release builds do
+//! not pay for measuring every poll of every task.
+
+use super::AbortHandle;
+use std::{cell::RefCell, fmt, panic::Location};
+
+#[cfg(debug_assertions)]
+use pin_project::{pin_project, pinned_drop};
+#[cfg(debug_assertions)]
+use std::{
+    collections::BTreeMap,
+    future::Future,
+    pin::Pin,
+    task,
+    time::Duration,
+};
+
+#[cfg(all(debug_assertions, feature = "time"))]
+use crate::time::Instant;
+
+/// Unique identifier of a task spawned in the current WASM instance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TaskId(u64);
+
+impl fmt::Display for TaskId {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmtr, "#{}", self.0)
+    }
+}
+
+/// Current state of a live task.
+#[cfg(debug_assertions)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TaskState {
+    /// The task was spawned, but it was not polled yet.
+    Spawned,
+    /// The task is being polled right now.
+    Running,
+    /// The task was polled and it is waiting to be woken.
+    Waiting,
+    /// The task was requested to abort, but its future was not dropped yet.
+    Aborting,
+}
+
+#[cfg(debug_assertions)]
+impl fmt::Display for TaskState {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Self::Spawned => "spawned",
+            Self::Running => "running",
+            Self::Waiting => "waiting",
+            Self::Aborting => "aborting",
+        };
+        fmtr.pad(name)
+    }
+}
+
+#[cfg(debug_assertions)]
+/// A snapshot of the information about a live task, as listed by [`tasks`].
+#[derive(Debug, Clone)]
+pub struct TaskInfo {
+    id: TaskId,
+    name: Option<String>,
+    location: &'static Location<'static>,
+    poll_count: u64,
+    poll_time: Duration,
+    state: TaskState,
+}
+
+#[cfg(debug_assertions)]
+impl TaskInfo {
+    /// The identifier of the task.
+    pub fn id(&self) -> TaskId {
+        self.id
+    }
+
+    /// The name of the task, if it was given one through
+    /// [`Builder::name`](super::Builder::name).
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Location in the source code where the task was spawned.
+    pub fn location(&self) -> &'static Location<'static> {
+        self.location
+    }
+
+    /// How many times the task's future was polled.
+    pub fn poll_count(&self) -> u64 {
+        self.poll_count
+    }
+
+    /// Total time spent polling the task's future. Always zero if the `time`
+    /// feature is disabled.
+    pub fn poll_time(&self) -> Duration {
+        self.poll_time
+    }
+
+    /// The state of the task when this snapshot was taken.
+    pub fn state(&self) -> TaskState {
+        self.state
+    }
+}
+
+#[cfg(debug_assertions)]
+impl fmt::Display for TaskInfo {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmtr, "task {}", self.id)?;
+        if let Some(name) = &self.name {
+            write!(fmtr, " {:?}", name)?;
+        }
+        write!(
+            fmtr,
+            " spawned at {}: {}, polled {} times for {:?}",
+            self.location, self.state, self.poll_count, self.poll_time
+        )
+    }
+}
+
+#[cfg(debug_assertions)]
+struct Record {
+    name: Option<String>,
+    location: &'static Location<'static>,
+    poll_count: u64,
+    poll_time: Duration,
+    running: bool,
+    abort_handle: AbortHandle,
+}
+
+#[cfg(debug_assertions)]
+impl Record {
+    fn state(&self) -> TaskState {
+        if self.running {
+            TaskState::Running
+        } else if self.abort_handle.is_aborted() {
+            TaskState::Aborting
+        } else if self.poll_count == 0 {
+            TaskState::Spawned
+        } else {
+            TaskState::Waiting
+        }
+    }
+}
+
+#[derive(Default)]
+struct Registry {
+    next_id: u64,
+    #[cfg(debug_assertions)]
+    records: BTreeMap<TaskId, Record>,
+}
+
+thread_local! {
+    static REGISTRY: RefCell<Registry> = RefCell::new(Registry::default());
+}
+
+pub(super) fn register(
+    name: Option<String>,
+    location: &'static Location<'static>,
+    abort_handle: AbortHandle,
+) -> TaskId {
+    REGISTRY.with(|registry| {
+        let mut registry = registry.borrow_mut();
+        let id = TaskId(registry.next_id);
+        registry.next_id += 1;
+        #[cfg(debug_assertions)]
+        registry.records.insert(
+            id,
+            Record {
+                name,
+                location,
+                poll_count: 0,
+                poll_time: Duration::ZERO,
+                running: false,
+                abort_handle,
+            },
+        );
+        #[cfg(not(debug_assertions))]
+        let _ = (name, location, abort_handle);
+        id
+    })
+}
+
+#[cfg(debug_assertions)]
+fn with_record<F>(id: TaskId, visitor: F)
+where
+    F: FnOnce(&mut Record),
+{
+    REGISTRY.with(|registry| {
+        if let Some(record) = registry.borrow_mut().records.get_mut(&id) {
+            visitor(record);
+        }
+    })
+}
+
+/// Lists all live tasks, i.e. tasks spawned by this crate that were not
+/// completed, aborted or dropped yet, in the order they were spawned. Only
+/// available in debug builds.
+///
+/// # Examples
+/// ```ignore
+/// use webio::task;
+///
+/// # fn main() {
+/// # task::detach(async {
+/// let handle = task::Builder::new()
+///     .name("background sync")
+///     .spawn(std::future::pending::<()>());
+/// let tasks = task::tasks();
+/// assert!(tasks.iter().any(|info| info.name() == Some("background sync")));
+/// # });
+/// # }
+/// ```
+#[cfg(debug_assertions)]
+#[cfg_attr(feature = "feature-doc-cfg", doc(cfg(debug_assertions)))]
+pub fn tasks() -> Vec<TaskInfo> {
+    REGISTRY.with(|registry| {
+        registry
+            .borrow()
+            .records
+            .iter()
+            .map(|(id, record)| TaskInfo {
+                id: *id,
+                name: record.name.clone(),
+                location: record.location,
+                poll_count: record.poll_count,
+                poll_time: record.poll_time,
+                state: record.state(),
+            })
+            .collect()
+    })
+}
+
+/// Dumps all live tasks (see [`tasks`]) to the JavaScript/browser/node
+/// console, or to the standard error on native targets, one line per task.
+/// Only available in debug builds.
+#[cfg(all(debug_assertions, feature = "macros"))]
+#[cfg_attr(
+    feature = "feature-doc-cfg",
+    doc(cfg(all(debug_assertions, feature = "macros")))
+)]
+pub fn dump_tasks() {
+    let tasks = tasks();
+    dump_line(format!("{} live task(s)", tasks.len()));
+    for info in tasks {
+        dump_line(info.to_string());
+    }
+}
+
+#[cfg(all(debug_assertions, feature = "macros", target_arch = "wasm32"))]
+fn dump_line(line: String) {
+    crate::console_debug!(line);
+}
+
+#[cfg(all(debug_assertions, feature = "macros", not(target_arch = "wasm32")))]
+fn dump_line(line: String) {
+    eprintln!("{}", line);
+}
+
+/// Wraps a task's future so that its polls are recorded in the registry.
+#[cfg(debug_assertions)]
+pub(super) fn instrument<A>(id: TaskId, future: A) -> Instrumented<A> {
+    Instrumented { id, future }
+}
+
+/// Leaves a task's future as is, since tasks are not recorded in release
+/// builds.
+#[cfg(not(debug_assertions))]
+pub(super) fn instrument<A>(_id: TaskId, future: A) -> Instrumented<A> {
+    future
+}
+
+#[cfg(not(debug_assertions))]
+pub(super) type Instrumented<A> = A;
+
+#[cfg(debug_assertions)]
+#[pin_project(PinnedDrop)]
+pub(super) struct Instrumented<A> {
+    id: TaskId,
+    #[pin]
+    future: A,
+}
+
+#[cfg(debug_assertions)]
+impl<A> Future for Instrumented<A>
+where
+    A: Future,
+{
+    type Output = A::Output;
+
+    fn poll(
+        self: Pin<&mut Self>,
+        ctx: &mut task::Context<'_>,
+    ) -> task::Poll<Self::Output> {
+        let this = self.project();
+        let id = *this.id;
+        with_record(id, |record| record.running = true);
+        #[cfg(feature = "time")]
+        let start = Instant::now();
+        let poll = this.future.poll(ctx);
+        #[cfg(feature = "time")]
+        let elapsed = start.elapsed();
+        #[cfg(not(feature = "time"))]
+        let elapsed = Duration::ZERO;
+        with_record(id, |record| {
+            record.running = false;
+            record.poll_count += 1;
+            record.poll_time += elapsed;
+        });
+        poll
+    }
+}
+
+#[cfg(debug_assertions)]
+#[pinned_drop]
+impl<A> PinnedDrop for Instrumented<A> {
+    fn drop(self: Pin<&mut Self>) {
+        let id = self.id;
+        let _ = REGISTRY
+            .try_with(|registry| registry.borrow_mut().records.remove(&id));
+    }
+}
@@ -0,0 +1,226 @@
+//! Implementation of a webio-owned executor where tasks have priorities, built
+//! on the Prioritized Task Scheduling API when available.
+
+use super::{budget, yield_with, TaskFuture, YieldStrategy};
+use crate::time::Instant;
+use std::{
+    cell::{Cell, RefCell},
+    collections::{HashMap, VecDeque},
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task,
+};
+
+#[cfg(target_arch = "wasm32")]
+use js_sys::{Function, Object, Reflect};
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+
+/// Priority of a task spawned through [`Builder::priority`](super::Builder),
+/// mirroring the priorities of the Prioritized Task Scheduling API. Whenever
+/// tasks of different priorities are ready to run, tasks of higher priority
+/// run first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum Priority {
+    /// Tasks that are critical to user interaction, such as handling input.
+    UserBlocking,
+    /// Tasks that produce outcomes visible to the user, but not critical to
+    /// user interaction.
+    #[default]
+    UserVisible,
+    /// Tasks that are not time critical, such as background work.
+    Background,
+}
+
+impl Priority {
+    const COUNT: usize = 3;
+
+    /// The name of this priority in the Prioritized Task Scheduling API.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::UserBlocking => "user-blocking",
+            Self::UserVisible => "user-visible",
+            Self::Background => "background",
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+type TaskId = u64;
+
+struct Entry {
+    future: Option<Pin<Box<TaskFuture>>>,
+    priority: Priority,
+    queued: bool,
+}
+
+#[derive(Default)]
+struct Executor {
+    next_id: Cell<TaskId>,
+    tasks: RefCell<HashMap<TaskId, Entry>>,
+    queues: RefCell<[VecDeque<TaskId>; Priority::COUNT]>,
+    pump_scheduled: Cell<bool>,
+}
+
+impl Drop for Executor {
+    fn drop(&mut self) {
+        // Tasks still alive when the thread exits are leaked, as dropping them
+        // could access thread-local storage that is already destroyed.
+        for (_, entry) in self.tasks.get_mut().drain() {
+            std::mem::forget(entry);
+        }
+    }
+}
+
+thread_local! {
+    static EXECUTOR: Executor = Executor::default();
+}
+
+struct TaskWaker {
+    id: TaskId,
+}
+
+impl task::Wake for TaskWaker {
+    fn wake(self: Arc<Self>) {
+        schedule(self.id);
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        schedule(self.id);
+    }
+}
+
+pub(super) fn spawn(priority: Priority, future: TaskFuture) {
+    let id = EXECUTOR.with(|executor| {
+        let id = executor.next_id.get();
+        executor.next_id.set(id + 1);
+        let entry =
+            Entry { future: Some(Box::pin(future)), priority, queued: false };
+        executor.tasks.borrow_mut().insert(id, entry);
+        id
+    });
+    schedule(id);
+}
+
+#[cfg(target_arch = "wasm32")]
+fn post_task_function() -> Option<(JsValue, Function)> {
+    let scheduler =
+        Reflect::get(&js_sys::global(), &JsValue::from_str("scheduler"))
+            .ok()
+            .filter(|scheduler| scheduler.is_object())?;
+    let post_task = Reflect::get(&scheduler, &JsValue::from_str("postTask"))
+        .ok()?
+        .dyn_into::<Function>()
+        .ok()?;
+    Some((scheduler, post_task))
+}
+
+fn schedule(id: TaskId) {
+    let priority = EXECUTOR.with(|executor| {
+        let mut tasks = executor.tasks.borrow_mut();
+        let entry = tasks.get_mut(&id)?;
+        if entry.queued {
+            None
+        } else {
+            entry.queued = true;
+            Some(entry.priority)
+        }
+    });
+    let Some(priority) = priority else { return };
+
+    if post_task(id, priority) {
+        return;
+    }
+
+    EXECUTOR.with(|executor| {
+        executor.queues.borrow_mut()[priority.index()].push_back(id);
+        if !executor.pump_scheduled.replace(true) {
+            super::detach(async {
+                yield_with(YieldStrategy::Macrotask).await;
+                pump();
+            });
+        }
+    });
+}
+
+/// Posts the given task to `scheduler.postTask`, returning whether it was
+/// posted.
+#[cfg(target_arch = "wasm32")]
+fn post_task(id: TaskId, priority: Priority) -> bool {
+    let Some((scheduler, post_task)) = post_task_function() else {
+        return false;
+    };
+    let closure = Closure::once_into_js(move || run(id));
+    let options = Object::new();
+    Reflect::set(
+        &options,
+        &JsValue::from_str("priority"),
+        &JsValue::from_str(priority.as_str()),
+    )
+    .unwrap();
+    post_task.call2(&scheduler, &closure, &options).is_ok()
+}
+
+/// There is no `scheduler.postTask` on native targets.
+#[cfg(not(target_arch = "wasm32"))]
+fn post_task(_id: TaskId, _priority: Priority) -> bool {
+    false
+}
+
+fn pump() {
+    let start = Instant::now();
+    loop {
+        let next = EXECUTOR.with(|executor| {
+            executor
+                .queues
+                .borrow_mut()
+                .iter_mut()
+                .find_map(VecDeque::pop_front)
+        });
+        match next {
+            Some(id) => run(id),
+            None => break,
+        }
+        if start.elapsed() >= budget() {
+            break;
+        }
+    }
+
+    EXECUTOR.with(|executor| {
+        executor.pump_scheduled.set(false);
+        if executor.queues.borrow().iter().any(|queue| !queue.is_empty()) {
+            executor.pump_scheduled.set(true);
+            super::detach(async {
+                yield_with(YieldStrategy::Macrotask).await;
+                pump();
+            });
+        }
+    });
+}
+
+fn run(id: TaskId) {
+    let future = EXECUTOR.with(|executor| {
+        let mut tasks = executor.tasks.borrow_mut();
+        let entry = tasks.get_mut(&id)?;
+        entry.queued = false;
+        entry.future.take()
+    });
+    let Some(mut future) = future else { return };
+
+    let waker = task::Waker::from(Arc::new(TaskWaker { id }));
+    let mut ctx = task::Context::from_waker(&waker);
+    let poll = future.as_mut().poll(&mut ctx);
+
+    EXECUTOR.with(|executor| {
+        let mut tasks = executor.tasks.borrow_mut();
+        if poll.is_ready() {
+            tasks.remove(&id);
+        } else if let Some(entry) = tasks.get_mut(&id) {
+            entry.future = Some(future);
+        }
+    });
+}
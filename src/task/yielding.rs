@@ -0,0 +1,138 @@
+//! Implementation of the strategies of yielding control back to the browser.
+
+use crate::{task, time};
+use std::time::Duration;
+
+#[cfg(target_arch = "wasm32")]
+use crate::callback;
+#[cfg(target_arch = "wasm32")]
+use js_sys::{Function, Promise, Reflect};
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::{closure::Closure, prelude::wasm_bindgen, JsCast, JsValue};
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen_futures::JsFuture;
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(extends = ::js_sys::Object, js_name = MessageChannel)]
+    type MessageChannel;
+
+    #[wasm_bindgen(constructor, js_class = MessageChannel)]
+    fn new() -> MessageChannel;
+
+    #[wasm_bindgen(method, structural, getter)]
+    fn port1(this: &MessageChannel) -> MessagePort;
+
+    #[wasm_bindgen(method, structural, getter)]
+    fn port2(this: &MessageChannel) -> MessagePort;
+
+    #[wasm_bindgen(extends = ::js_sys::Object, js_name = MessagePort)]
+    type MessagePort;
+
+    #[wasm_bindgen(method, structural, setter)]
+    fn set_onmessage(this: &MessagePort, handler: &Function);
+
+    #[wasm_bindgen(method, structural, js_name = postMessage)]
+    fn post_message(this: &MessagePort, message: &JsValue);
+
+    #[wasm_bindgen(method, structural)]
+    fn close(this: &MessagePort);
+}
+
+/// A strategy of yielding control back to the browser, used by [`yield_with`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum YieldStrategy {
+    /// Yields through the microtask queue, just like [`task::yield_now`].
+    /// Other tasks get to run, but the browser neither renders nor handles
+    /// input before execution resumes.
+    #[default]
+    Microtask,
+    /// Yields through the macrotask queue, by posting a message to a
+    /// `MessageChannel`, which is not subject to the clamping of `setTimeout`.
+    /// The browser gets to render and handle input before execution resumes.
+    /// Falls back to `setTimeout` where `MessageChannel` is not available, as
+    /// well as on native targets.
+    Macrotask,
+    /// Yields through `scheduler.yield()` of the Prioritized Task Scheduling
+    /// API, which resumes execution ahead of other pending macrotasks. Falls
+    /// back to [`YieldStrategy::Macrotask`] where the API is not available.
+    Scheduler,
+}
+
+/// Yields control back to the event loop once using the given strategy, and
+/// returns back to execution afterwards.
+///
+/// # Examples
+///
+/// ```no_run
+/// use webio::task::{self, YieldStrategy};
+///
+/// # fn main() {
+/// # task::detach(async {
+/// # fn render_step() -> bool { false }
+/// while render_step() {
+///     // Lets the browser paint between steps.
+///     task::yield_with(YieldStrategy::Macrotask).await;
+/// }
+/// # });
+/// # }
+/// ```
+pub async fn yield_with(strategy: YieldStrategy) {
+    match strategy {
+        YieldStrategy::Microtask => task::yield_now().await,
+        YieldStrategy::Macrotask => yield_macrotask().await,
+        YieldStrategy::Scheduler => yield_scheduler().await,
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn global_property(name: &str) -> Option<JsValue> {
+    Reflect::get(&js_sys::global(), &JsValue::from_str(name))
+        .ok()
+        .filter(|value| !value.is_undefined() && !value.is_null())
+}
+
+#[cfg(target_arch = "wasm32")]
+async fn yield_macrotask() {
+    if global_property("MessageChannel").is_none() {
+        time::real_timeout(Duration::ZERO).await;
+        return;
+    }
+
+    let channel = MessageChannel::new();
+    let register = callback::once::SyncRegister::new(|callback| {
+        let closure = Closure::once_into_js(move || callback(()));
+        channel.port1().set_onmessage(closure.unchecked_ref());
+        channel.port2().post_message(&JsValue::UNDEFINED);
+    });
+    let _ = register.listen(|()| ()).await;
+    channel.port1().close();
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+async fn yield_macrotask() {
+    time::real_timeout(Duration::ZERO).await;
+}
+
+#[cfg(target_arch = "wasm32")]
+async fn yield_scheduler() {
+    let promise = global_property("scheduler").and_then(|scheduler| {
+        let function = Reflect::get(&scheduler, &JsValue::from_str("yield"))
+            .ok()?
+            .dyn_into::<Function>()
+            .ok()?;
+        function.call0(&scheduler).ok()?.dyn_into::<Promise>().ok()
+    });
+    match promise {
+        Some(promise) => {
+            let _ = JsFuture::from(promise).await;
+        },
+        None => yield_macrotask().await,
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+async fn yield_scheduler() {
+    yield_macrotask().await
+}
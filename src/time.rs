@@ -1,53 +1,129 @@
 //! This module implements time-related utilities.
 
 mod instant;
+mod timer;
+mod animation_frame;
+mod idle;
+mod interval;
+#[cfg(any(feature = "event", feature = "stream"))]
+mod rate_limit;
+mod system_time;
+mod with_timeout;
 
-use crate::callback;
-use js_sys::Function;
-use pin_project::{pin_project, pinned_drop};
-use std::{future::Future, pin::Pin, task, time::Duration};
-use wasm_bindgen::{closure::Closure, prelude::wasm_bindgen, JsCast, JsValue};
+#[cfg(feature = "mock")]
+#[cfg_attr(feature = "feature-doc-cfg", doc(cfg(feature = "mock")))]
+pub mod mock;
 
-#[cfg(feature = "stream")]
-use futures::stream::Stream;
+pub mod perf;
 
+use crate::callback;
+use pin_project::pin_project;
+use std::{future::Future, task, time::Duration};
+use timer::Timer;
+
+pub use animation_frame::{
+    animation_frame,
+    animation_frames,
+    AnimationFrameHandle,
+    AnimationFrameTick,
+    AnimationFrames,
+    Frame,
+};
+pub use idle::{idle, idle_with_timeout, IdleDeadline, IdleHandle};
 pub use instant::Instant;
+pub use interval::{
+    interval,
+    IntervalHandle,
+    IntervalTick,
+    MissedTickBehavior,
+};
+#[cfg(target_arch = "wasm32")]
+pub use system_time::InvalidDate;
+pub use system_time::{SystemTime, SystemTimeError, UNIX_EPOCH};
+pub use with_timeout::{with_timeout, Elapsed, Timeout, TimeoutExt};
+
+#[cfg(feature = "event")]
+pub(crate) use rate_limit::{Debouncer, Throttler};
 
-#[wasm_bindgen]
-extern "C" {
-    #[wasm_bindgen(js_name = "setTimeout")]
-    fn set_timeout(function: &Function, milliseconds: i32) -> JsValue;
-    #[wasm_bindgen(js_name = "clearTimeout")]
-    fn clear_timeout(timeout_id: &JsValue);
-
-    #[wasm_bindgen(js_name = "setInterval")]
-    fn set_interval(function: &Function, milliseconds: i32) -> JsValue;
-    #[wasm_bindgen(js_name = "clearInterval")]
-    fn clear_interval(interval_id: &JsValue);
-}
+#[cfg(feature = "stream")]
+#[cfg_attr(feature = "feature-doc-cfg", doc(cfg(feature = "stream")))]
+pub use rate_limit::{Debounce, Throttle, TimeStreamExt};
 
+#[cfg(target_arch = "wasm32")]
 fn duration_to_millis(duration: Duration) -> i32 {
-    duration.as_millis().min(i32::MAX as u128) as i32
+    // Rounds up, so that timers never complete before their deadline.
+    duration.as_nanos().div_ceil(1_000_000).min(i32::MAX as u128) as i32
 }
 
-/// A handle to a [`timeout`] call. The timeout can be waited through `.await`,
-/// or it can be cancelled when the handle is dropped without the timeout
-/// completing.
-#[pin_project(PinnedDrop)]
+/// A handle to a [`timeout`] or [`sleep_until`] call. The timeout can be waited
+/// through `.await`, or it can be cancelled when the handle is dropped without
+/// the timeout completing. Its deadline can be changed through
+/// [`reset`](TimeoutHandle::reset).
+#[derive(Debug)]
+#[pin_project]
 pub struct TimeoutHandle {
     #[pin]
     listener: callback::once::Listener<()>,
-    timeout_id: JsValue,
-    _closure: JsValue,
+    _timer: Timer,
+    deadline: Instant,
+    real: bool,
+    waker: Option<task::Waker>,
 }
 
 impl TimeoutHandle {
-    fn new(
-        listener: callback::once::Listener<()>,
-        timeout_id: JsValue,
-        closure: JsValue,
-    ) -> Self {
-        Self { listener, timeout_id, _closure: closure }
+    fn new(deadline: Instant, delay: Duration, real: bool) -> Self {
+        let (timer, listener) = Self::schedule(delay, real);
+        Self { listener, _timer: timer, deadline, real, waker: None }
+    }
+
+    fn schedule(
+        delay: Duration,
+        real: bool,
+    ) -> (Timer, callback::once::Listener<()>) {
+        let register = callback::once::SyncRegister::new(|callback| {
+            let callback = move || callback(());
+            if real {
+                Timer::real_timeout(delay, callback)
+            } else {
+                Timer::timeout(delay, callback)
+            }
+        });
+        register.listen_returning(|()| ())
+    }
+
+    /// Returns the instant at which this timeout completes.
+    pub fn deadline(&self) -> Instant {
+        self.deadline
+    }
+
+    /// Changes the deadline of this timeout, rescheduling the underlying timer.
+    /// The timeout can be waited again even if it has already completed.
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use webio::time::{timeout, Instant};
+    ///
+    /// # use webio::task;
+    /// # fn main() {
+    /// # task::detach(async {
+    /// let then = Instant::now();
+    /// let mut handle = timeout(Duration::from_millis(100));
+    /// handle.reset(handle.deadline() + Duration::from_millis(100));
+    /// handle.await;
+    /// assert!(then.elapsed() >= Duration::from_millis(200));
+    /// # });
+    /// # }
+    /// ```
+    pub fn reset(&mut self, deadline: Instant) {
+        let now = if self.real { Instant::real_now() } else { Instant::now() };
+        let delay = deadline.saturating_duration_since(now);
+        let (timer, listener) = Self::schedule(delay, self.real);
+        self.listener = listener;
+        self._timer = timer;
+        self.deadline = deadline;
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
     }
 }
 
@@ -58,14 +134,18 @@ impl Future for TimeoutHandle {
         self: std::pin::Pin<&mut Self>,
         ctx: &mut task::Context<'_>,
     ) -> task::Poll<Self::Output> {
-        self.project().listener.poll(ctx).map(|result| result.unwrap())
-    }
-}
-
-#[pinned_drop]
-impl PinnedDrop for TimeoutHandle {
-    fn drop(self: Pin<&mut Self>) {
-        clear_timeout(&self.timeout_id);
+        let this = self.project();
+        let poll = this.listener.poll(ctx).map(|result| result.unwrap());
+        if poll.is_ready() {
+            *this.waker = None;
+        } else if !this
+            .waker
+            .as_ref()
+            .is_some_and(|waker| waker.will_wake(ctx.waker()))
+        {
+            *this.waker = Some(ctx.waker().clone());
+        }
+        poll
     }
 }
 
@@ -89,125 +169,32 @@ impl PinnedDrop for TimeoutHandle {
 /// # }
 /// ```
 pub fn timeout(duration: Duration) -> TimeoutHandle {
-    timeout_ms(duration_to_millis(duration))
-}
-
-fn timeout_ms(milliseconds: i32) -> TimeoutHandle {
-    let register = callback::once::SyncRegister::new(|callback| {
-        let closure = Closure::once_into_js(move || callback(()));
-        let timeout_id = set_timeout(closure.dyn_ref().unwrap(), milliseconds);
-        (timeout_id, closure)
-    });
-
-    let ((id, closure), listener) = register.listen_returning(|()| ());
-
-    TimeoutHandle::new(listener, id, closure)
-}
-
-/// A handle to an [`interval`] call. An interval can be waited through
-/// `.tick().await`, or it can be cancelled when the handle is dropped without
-/// the interval completing.
-pub struct IntervalHandle {
-    listener: callback::multi::Listener<()>,
-    interval_id: JsValue,
-    _closure: JsValue,
-}
-
-impl IntervalHandle {
-    fn new(
-        listener: callback::multi::Listener<()>,
-        interval_id: JsValue,
-        closure: JsValue,
-    ) -> Self {
-        Self { listener, interval_id, _closure: closure }
-    }
-
-    /// Ticks for the next interval. This is an asynchronous function.
-    pub fn tick<'this>(&'this self) -> IntervalTick<'this> {
-        IntervalTick { listener: self.listener.listen_next() }
-    }
-}
-
-impl Drop for IntervalHandle {
-    fn drop(&mut self) {
-        clear_interval(&self.interval_id);
-    }
-}
-
-#[cfg(feature = "stream")]
-impl Stream for IntervalHandle {
-    type Item = ();
-
-    fn poll_next(
-        mut self: Pin<&mut Self>,
-        ctx: &mut task::Context<'_>,
-    ) -> task::Poll<Option<Self::Item>> {
-        Pin::new(&mut self.listener).poll_next(ctx)
-    }
+    TimeoutHandle::new(Instant::now() + duration, duration, false)
 }
 
-/// A single interval tick that can be awaited.
-pub struct IntervalTick<'handle> {
-    listener: callback::multi::ListenNext<'handle, ()>,
-}
-
-impl<'handle> Future for IntervalTick<'handle> {
-    type Output = ();
-
-    fn poll(
-        mut self: std::pin::Pin<&mut Self>,
-        ctx: &mut task::Context<'_>,
-    ) -> task::Poll<Self::Output> {
-        Pin::new(&mut self.listener).poll(ctx).map(Result::unwrap)
-    }
-}
-
-/// Creates a handle that produces [`Future`]s that, when awaited, are always
-/// completed approximately with the same interval between them, they "tick",
-/// given by a `duration`.
+/// Creates a [`Future`] that completes only once the given deadline is
+/// reached. Completes as soon as possible if the deadline is in the past.
 ///
 /// ```no_run
 /// use std::time::Duration;
-/// use webio::time::{interval, Instant};
+/// use webio::time::{sleep_until, Instant};
 ///
 /// # use webio::task;
 /// # fn main() {
 /// # task::detach(async {
-/// let time = Duration::from_millis(100);
-/// let handle = interval(time);
-/// let then = Instant::now();
-///
-/// handle.tick().await;
-/// let passed = then.elapsed();
-/// assert!(passed >= time - Duration::from_millis(50));
-/// assert!(passed < time + Duration::from_millis(50));
-///
-/// handle.tick().await;
-/// let passed = then.elapsed();
-/// assert!(passed >= time * 2 - Duration::from_millis(50));
-/// assert!(passed < time * 2 + Duration::from_millis(50));
-///
-/// handle.tick().await;
-/// let passed = then.elapsed();
-/// assert!(passed >= time * 3 - Duration::from_millis(50));
-/// assert!(passed < time * 3 + Duration::from_millis(50));
+/// let deadline = Instant::now() + Duration::from_millis(200);
+/// sleep_until(deadline).await;
+/// assert!(Instant::now() >= deadline);
 /// # });
 /// # }
 /// ```
-pub fn interval(duration: Duration) -> IntervalHandle {
-    interval_ms(duration_to_millis(duration))
+pub fn sleep_until(deadline: Instant) -> TimeoutHandle {
+    let delay = deadline.saturating_duration_since(Instant::now());
+    TimeoutHandle::new(deadline, delay, false)
 }
 
-fn interval_ms(milliseconds: i32) -> IntervalHandle {
-    let register = callback::multi::SyncRegister::new(|mut callback| {
-        let boxed_callback = Box::new(move || callback(()));
-        let closure =
-            Closure::wrap(boxed_callback as Box<dyn FnMut()>).into_js_value();
-        let timeout_id = set_interval(closure.dyn_ref().unwrap(), milliseconds);
-        (timeout_id, closure)
-    });
-
-    let ((id, closure), listener) = register.listen_returning(|()| ());
-
-    IntervalHandle::new(listener, id, closure)
+/// Like [`timeout`], but always on the real clock, even if time is paused by
+/// the mock clock. Used for scheduling rather than for measuring time.
+pub(crate) fn real_timeout(duration: Duration) -> TimeoutHandle {
+    TimeoutHandle::new(Instant::real_now() + duration, duration, true)
 }
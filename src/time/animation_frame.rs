@@ -0,0 +1,229 @@
+//! Implementation of futures driven by `requestAnimationFrame`.
+
+use super::Instant;
+use crate::callback;
+use pin_project::pin_project;
+use std::{cell::Cell, future::Future, pin::Pin, task, time::Duration};
+
+#[cfg(target_arch = "wasm32")]
+use js_sys::Function;
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::{closure::Closure, prelude::wasm_bindgen, JsCast, JsValue};
+
+#[cfg(not(target_arch = "wasm32"))]
+use super::Timer;
+
+#[cfg(feature = "stream")]
+use futures::stream::Stream;
+
+/// Period between frames emulated on native targets, where there is no
+/// repaint, i.e. 60 frames per second.
+#[cfg(not(target_arch = "wasm32"))]
+const NATIVE_FRAME_PERIOD: Duration = Duration::from_nanos(16_666_667);
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_name = "requestAnimationFrame")]
+    fn request_animation_frame(function: &Function) -> JsValue;
+    #[wasm_bindgen(js_name = "cancelAnimationFrame")]
+    fn cancel_animation_frame(request_id: &JsValue);
+}
+
+/// A handle to an [`animation_frame`] call. The frame can be waited through
+/// `.await`, yielding the frame's timestamp, or it can be cancelled when the
+/// handle is dropped without the frame arriving.
+#[derive(Debug)]
+#[pin_project]
+pub struct AnimationFrameHandle {
+    #[pin]
+    listener: callback::once::Listener<Instant>,
+    _request: Request,
+}
+
+#[cfg(target_arch = "wasm32")]
+#[derive(Debug)]
+struct Request {
+    request_id: JsValue,
+    _closure: JsValue,
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Drop for Request {
+    fn drop(&mut self) {
+        cancel_animation_frame(&self.request_id);
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug)]
+struct Request {
+    _timer: Timer,
+}
+
+impl Future for AnimationFrameHandle {
+    type Output = Instant;
+
+    fn poll(
+        self: Pin<&mut Self>,
+        ctx: &mut task::Context<'_>,
+    ) -> task::Poll<Self::Output> {
+        self.project().listener.poll(ctx).map(|result| result.unwrap())
+    }
+}
+
+/// Creates a [`Future`] that completes right before the browser's next repaint,
+/// yielding the frame's high-resolution timestamp. On native targets, frames
+/// are emulated at 60 frames per second.
+///
+/// ```no_run
+/// use webio::time::{animation_frame, Instant};
+///
+/// # use webio::task;
+/// # fn main() {
+/// # task::detach(async {
+/// let then = Instant::now();
+/// let timestamp = animation_frame().await;
+/// assert!(timestamp >= then);
+/// # });
+/// # }
+/// ```
+pub fn animation_frame() -> AnimationFrameHandle {
+    let register = callback::once::SyncRegister::new(request_frame);
+    let (request, listener) = register.listen_returning(|instant| instant);
+    AnimationFrameHandle { listener, _request: request }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn request_frame<F>(callback: F) -> Request
+where
+    F: FnOnce(Instant) + 'static,
+{
+    let closure = Closure::once_into_js(move |timestamp: f64| {
+        callback(Instant::from_dom_high_res_timestamp(timestamp))
+    });
+    let request_id = request_animation_frame(closure.dyn_ref().unwrap());
+    Request { request_id, _closure: closure }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn request_frame<F>(callback: F) -> Request
+where
+    F: FnOnce(Instant) + 'static,
+{
+    let period = NATIVE_FRAME_PERIOD.as_nanos();
+    let elapsed = crate::native::now().as_nanos();
+    let delay = Duration::from_nanos((period - elapsed % period) as u64);
+    let timer = Timer::timeout(delay, move || callback(Instant::now()));
+    Request { _timer: timer }
+}
+
+/// A single animation frame, yielded by [`AnimationFrames`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Frame {
+    timestamp: Instant,
+    delta: Duration,
+}
+
+impl Frame {
+    /// The high-resolution timestamp of this frame.
+    pub fn timestamp(&self) -> Instant {
+        self.timestamp
+    }
+
+    /// Time elapsed since the previous frame yielded by the same handle, or
+    /// zero if this is the first frame.
+    pub fn delta(&self) -> Duration {
+        self.delta
+    }
+}
+
+/// A handle to an [`animation_frames`] call. Frames can be waited through
+/// `.tick().await`. A frame is only requested to the browser while a tick is
+/// being waited, and such request is cancelled when the tick is dropped.
+#[derive(Debug, Default)]
+pub struct AnimationFrames {
+    previous: Cell<Option<Instant>>,
+    #[cfg(feature = "stream")]
+    pending: Option<AnimationFrameHandle>,
+}
+
+impl AnimationFrames {
+    /// Ticks for the next frame. This is an asynchronous function.
+    pub fn tick<'this>(&'this self) -> AnimationFrameTick<'this> {
+        AnimationFrameTick { frames: self, request: animation_frame() }
+    }
+
+    fn make_frame(&self, timestamp: Instant) -> Frame {
+        let delta = self
+            .previous
+            .replace(Some(timestamp))
+            .map_or(Duration::ZERO, |previous| {
+                timestamp.saturating_duration_since(previous)
+            });
+        Frame { timestamp, delta }
+    }
+}
+
+#[cfg(feature = "stream")]
+impl Stream for AnimationFrames {
+    type Item = Frame;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        ctx: &mut task::Context<'_>,
+    ) -> task::Poll<Option<Self::Item>> {
+        let request = self.pending.get_or_insert_with(animation_frame);
+        let timestamp = match Pin::new(request).poll(ctx) {
+            task::Poll::Ready(timestamp) => timestamp,
+            task::Poll::Pending => return task::Poll::Pending,
+        };
+        self.pending = None;
+        task::Poll::Ready(Some(self.make_frame(timestamp)))
+    }
+}
+
+/// A single animation frame tick that can be awaited.
+#[pin_project]
+pub struct AnimationFrameTick<'frames> {
+    frames: &'frames AnimationFrames,
+    #[pin]
+    request: AnimationFrameHandle,
+}
+
+impl<'frames> Future for AnimationFrameTick<'frames> {
+    type Output = Frame;
+
+    fn poll(
+        self: Pin<&mut Self>,
+        ctx: &mut task::Context<'_>,
+    ) -> task::Poll<Self::Output> {
+        let this = self.project();
+        this.request
+            .poll(ctx)
+            .map(|timestamp| this.frames.make_frame(timestamp))
+    }
+}
+
+/// Creates a handle that produces [`Future`]s that, when awaited, complete
+/// right before the browser's next repaint, yielding the frame's timestamp and
+/// the time elapsed since the previous frame.
+///
+/// ```no_run
+/// use webio::time::animation_frames;
+///
+/// # use webio::task;
+/// # fn main() {
+/// # task::detach(async {
+/// let frames = animation_frames();
+/// let mut position = 0.0;
+/// for _ in 0 .. 60 {
+///     let frame = frames.tick().await;
+///     position += 100.0 * frame.delta().as_secs_f64();
+/// }
+/// # });
+/// # }
+/// ```
+pub fn animation_frames() -> AnimationFrames {
+    AnimationFrames::default()
+}
@@ -0,0 +1,218 @@
+//! Implementation of futures driven by `requestIdleCallback`.
+
+use super::{Instant, TimeoutHandle, Timer};
+use crate::callback;
+use pin_project::pin_project;
+use std::{future::Future, pin::Pin, task, time::Duration};
+
+#[cfg(target_arch = "wasm32")]
+use super::duration_to_millis;
+#[cfg(target_arch = "wasm32")]
+use js_sys::{Function, Object, Reflect};
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::{closure::Closure, prelude::wasm_bindgen, JsCast, JsValue};
+
+/// Idle period assumed when `requestIdleCallback` is not available and
+/// `setTimeout` is used instead.
+const FALLBACK_IDLE_PERIOD: Duration = Duration::from_millis(10);
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_name = "requestIdleCallback")]
+    fn request_idle_callback(function: &Function, options: &JsValue)
+        -> JsValue;
+    #[wasm_bindgen(js_name = "cancelIdleCallback")]
+    fn cancel_idle_callback(request_id: &JsValue);
+
+    #[wasm_bindgen(extends = ::js_sys::Object, js_name = IdleDeadline)]
+    type JsIdleDeadline;
+
+    #[wasm_bindgen(method, structural, js_name = timeRemaining)]
+    fn time_remaining(this: &JsIdleDeadline) -> f64;
+
+    #[wasm_bindgen(method, structural, getter, js_name = didTimeout)]
+    fn did_timeout(this: &JsIdleDeadline) -> bool;
+}
+
+#[cfg(target_arch = "wasm32")]
+fn supports_idle_callback() -> bool {
+    Reflect::get(&js_sys::global(), &JsValue::from_str("requestIdleCallback"))
+        .is_ok_and(|value| value.is_function())
+}
+
+/// The deadline of an idle period, i.e. until when the browser is expected to
+/// remain idle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct IdleDeadline {
+    deadline: Instant,
+    did_timeout: bool,
+}
+
+impl IdleDeadline {
+    #[cfg(target_arch = "wasm32")]
+    fn from_js(js_deadline: &JsIdleDeadline) -> Self {
+        let remaining = js_deadline.time_remaining().max(0.0);
+        Self {
+            deadline: Instant::now()
+                + Duration::from_secs_f64(remaining / 1000.0),
+            did_timeout: js_deadline.did_timeout(),
+        }
+    }
+
+    fn fallback(timeout_at: Option<Instant>) -> Self {
+        let now = Instant::now();
+        Self {
+            deadline: now + FALLBACK_IDLE_PERIOD,
+            did_timeout: timeout_at.is_some_and(|timeout_at| now >= timeout_at),
+        }
+    }
+
+    fn timed_out() -> Self {
+        Self { deadline: Instant::now(), did_timeout: true }
+    }
+
+    /// Returns how much time remains in the idle period, or zero if it is
+    /// already over.
+    pub fn time_remaining(&self) -> Duration {
+        self.deadline.saturating_duration_since(Instant::now())
+    }
+
+    /// Returns whether the idle period started because the timeout given to
+    /// [`idle_with_timeout`] expired, rather than because the browser became
+    /// idle.
+    pub fn did_timeout(&self) -> bool {
+        self.did_timeout
+    }
+}
+
+#[derive(Debug)]
+enum Request {
+    #[cfg(target_arch = "wasm32")]
+    Idle {
+        request_id: JsValue,
+        _closure: JsValue,
+    },
+    Timeout {
+        _timer: Timer,
+    },
+}
+
+impl Drop for Request {
+    fn drop(&mut self) {
+        #[cfg(target_arch = "wasm32")]
+        if let Self::Idle { request_id, .. } = self {
+            cancel_idle_callback(request_id);
+        }
+    }
+}
+
+/// A handle to an [`idle`] call. The idle period can be waited through
+/// `.await`, yielding its deadline, or it can be cancelled when the handle is
+/// dropped without the idle period starting.
+#[derive(Debug)]
+#[pin_project]
+pub struct IdleHandle {
+    #[pin]
+    listener: callback::once::Listener<IdleDeadline>,
+    _request: Request,
+    /// The timeout raced against the `setTimeout` fallback, which does not
+    /// support timeouts by itself.
+    #[pin]
+    timeout: Option<TimeoutHandle>,
+}
+
+impl Future for IdleHandle {
+    type Output = IdleDeadline;
+
+    fn poll(
+        self: Pin<&mut Self>,
+        ctx: &mut task::Context<'_>,
+    ) -> task::Poll<Self::Output> {
+        let this = self.project();
+        if let task::Poll::Ready(result) = this.listener.poll(ctx) {
+            return task::Poll::Ready(result.unwrap());
+        }
+        match this.timeout.as_pin_mut().map(|timeout| timeout.poll(ctx)) {
+            Some(task::Poll::Ready(())) => {
+                task::Poll::Ready(IdleDeadline::timed_out())
+            },
+            _ => task::Poll::Pending,
+        }
+    }
+}
+
+/// Creates a [`Future`] that completes when the browser becomes idle, yielding
+/// the deadline of the idle period. Built on `requestIdleCallback`, falling
+/// back to `setTimeout` where it is not available.
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use webio::time::idle;
+///
+/// # use webio::task;
+/// # fn main() {
+/// # task::detach(async {
+/// # fn index_next() -> bool { false }
+/// loop {
+///     let deadline = idle().await;
+///     while deadline.time_remaining() > Duration::from_millis(1) {
+///         if !index_next() {
+///             return;
+///         }
+///     }
+/// }
+/// # });
+/// # }
+/// ```
+pub fn idle() -> IdleHandle {
+    request_idle(None)
+}
+
+/// Creates a [`Future`] that completes when the browser becomes idle, or when
+/// the given timeout expires, whichever happens first, yielding the deadline of
+/// the idle period. Where `requestIdleCallback` is not available, the
+/// `setTimeout` fallback is raced against the timeout, so the timeout holds on
+/// every backend.
+pub fn idle_with_timeout(timeout: Duration) -> IdleHandle {
+    request_idle(Some(timeout))
+}
+
+fn request_idle(timeout: Option<Duration>) -> IdleHandle {
+    let register = callback::once::SyncRegister::new(|callback| {
+        #[cfg(target_arch = "wasm32")]
+        if supports_idle_callback() {
+            let closure =
+                Closure::once_into_js(move |js_deadline: JsIdleDeadline| {
+                    callback(IdleDeadline::from_js(&js_deadline))
+                });
+            let options = Object::new();
+            if let Some(timeout) = timeout {
+                Reflect::set(
+                    &options,
+                    &JsValue::from_str("timeout"),
+                    &JsValue::from(duration_to_millis(timeout)),
+                )
+                .unwrap();
+            }
+            let request_id =
+                request_idle_callback(closure.dyn_ref().unwrap(), &options);
+            return Request::Idle { request_id, _closure: closure };
+        }
+
+        let timeout_at = timeout.map(|timeout| Instant::now() + timeout);
+        let timer = Timer::real_timeout(Duration::ZERO, move || {
+            callback(IdleDeadline::fallback(timeout_at))
+        });
+        Request::Timeout { _timer: timer }
+    });
+
+    let (request, listener) = register.listen_returning(|deadline| deadline);
+    let timeout = match request {
+        Request::Timeout { .. } => timeout.map(super::timeout),
+        #[cfg(target_arch = "wasm32")]
+        Request::Idle { .. } => None,
+    };
+
+    IdleHandle { listener, _request: request, timeout }
+}
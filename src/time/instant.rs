@@ -6,12 +6,15 @@ use std::{
     ops::{Add, AddAssign, Sub, SubAssign},
     time::Duration,
 };
+
+#[cfg(target_arch = "wasm32")]
 use wasm_bindgen::{prelude::wasm_bindgen, JsCast};
 
 // I cannot simply declare `fn performance_now()` with
 // `(j́s_name = "now", namespace = "performance")` because wasm-pack generates
 // code that webpack does not handle correctly, probably because of hoisting.
 // And so, I need this workaround.
+#[cfg(target_arch = "wasm32")]
 #[wasm_bindgen]
 extern "C" {
     #[wasm_bindgen(extends = ::js_sys::Object, js_name = Object)]
@@ -27,6 +30,7 @@ extern "C" {
     fn now(this: &Performance) -> f64;
 }
 
+#[cfg(target_arch = "wasm32")]
 #[wasm_bindgen]
 extern "C" {
     #[wasm_bindgen(js_name = "now", js_namespace = performance)]
@@ -48,28 +52,106 @@ fn instant_is_infinite() -> ! {
 /// A montonic clock measurement, mimicking [`std::time::Instant`] but for WASM,
 /// which is not supported by std's `Instant`. Behind the curtains, this type
 /// uses JavaScript's `performance::now()`, so there is an overhead when
-/// calling [`Instant::now`].
-#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+/// calling [`Instant::now`]. On native targets, the clock of the
+/// [native backend](crate::native) is used instead.
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Instant {
     millis: f64,
 }
 
 impl Instant {
     /// Gets the clock measurement for this right moment. This uses JS
-    /// `performance_now`, so there might an overhead calling this method.
+    /// `performance_now`, so there might an overhead calling this method. On
+    /// native targets, the clock of the [native backend](crate::native) is
+    /// used instead. If time is paused by the [mock clock](super::mock), the
+    /// mock clock is used.
     pub fn now() -> Self {
+        let now = Self::real_now();
+        #[cfg(feature = "mock")]
+        let now = super::mock::adjust(now);
+        now
+    }
+
+    /// Gets the clock measurement for this right moment, ignoring the mock
+    /// clock.
+    #[cfg(target_arch = "wasm32")]
+    pub(crate) fn real_now() -> Self {
         let global = js_sys::global().dyn_into::<Global>().unwrap();
         let millis = global.performance().now();
         Self { millis }
     }
 
+    /// Gets the clock measurement for this right moment, ignoring the mock
+    /// clock.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn real_now() -> Self {
+        let millis = crate::native::now().as_secs_f64() * 1000.0;
+        Self { millis }
+    }
+
+    /// Creates an instant from a `DOMHighResTimeStamp`, i.e. milliseconds since
+    /// the time origin of the page, such as the value of `performance.now()`,
+    /// `Event.timeStamp` or the timestamp passed to `requestAnimationFrame`
+    /// callbacks. On native targets, the origin is the start of the clock of
+    /// the [native backend](crate::native). The timestamp is taken as is, even
+    /// if time is paused by the [mock clock](super::mock).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the timestamp is not finite, i.e. if it is NaN or infinite.
+    pub fn from_dom_high_res_timestamp(millis: f64) -> Self {
+        assert!(millis.is_finite(), "timestamp must be finite, got {}", millis);
+        Self { millis }
+    }
+
+    /// Returns this instant as milliseconds since the time origin of the page,
+    /// i.e. as a `DOMHighResTimeStamp`, comparable to values returned by
+    /// `performance.now()` and to `Event.timeStamp`.
+    pub fn as_millis_f64(self) -> f64 {
+        self.millis
+    }
+
+    /// Creates an instant from the timestamp of an event, i.e. the instant at
+    /// which the event was created.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use webio::{
+    ///     event::{Click, EventType},
+    ///     time::{animation_frame, Instant},
+    /// };
+    ///
+    /// # fn main() {
+    /// # webio::task::detach(async {
+    /// # let button = web_sys::window().unwrap().document().unwrap().body().unwrap();
+    /// let listener = Click.add_listener(&button);
+    /// let event = listener.listen_next().await.unwrap();
+    /// let clicked = Instant::from_event(&event);
+    /// // Updates the page in response to the click, then waits for the paint.
+    /// let painted = animation_frame().await;
+    /// let latency = painted.saturating_duration_since(clicked);
+    /// # });
+    /// # }
+    /// ```
+    #[cfg(feature = "event")]
+    #[cfg_attr(feature = "feature-doc-cfg", doc(cfg(feature = "event")))]
+    pub fn from_event<E>(event: &E) -> Self
+    where
+        E: AsRef<web_sys::Event>,
+    {
+        Self::from_dom_high_res_timestamp(event.as_ref().time_stamp())
+    }
+
     /// Returns the duration of time that passed from an earlier instant into
     /// this instant. If the `earlier` instant actually happened after the
     /// current instant, `None` is returned.
     pub fn checked_duration_since(self, earlier: Self) -> Option<Duration> {
         let millis = self.millis - earlier.millis;
         if millis >= 0.0 {
-            Some(Duration::from_secs_f64(millis / 1000.0))
+            // Rounded to nanoseconds, so that floating-point error does not
+            // leak into durations, e.g. when time is paused by the mock clock.
+            Some(Duration::from_nanos((millis * 1_000_000.0).round() as u64))
         } else {
             None
         }
@@ -161,6 +243,12 @@ impl SubAssign<Duration> for Instant {
 
 impl Eq for Instant {}
 
+impl PartialOrd for Instant {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 impl Ord for Instant {
     fn cmp(&self, other: &Self) -> Ordering {
         self.millis.partial_cmp(&other.millis).unwrap()
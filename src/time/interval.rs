@@ -0,0 +1,293 @@
+//! Implementation of intervals scheduled against [`Instant`] deadlines.
+
+use super::{sleep_until, Instant, TimeoutHandle};
+use crate::callback::{Buffer, BufferPolicy};
+use std::{
+    cell::{Cell, RefCell},
+    future::Future,
+    pin::Pin,
+    task,
+    time::Duration,
+};
+
+#[cfg(feature = "stream")]
+use futures::stream::Stream;
+
+/// What an [`IntervalHandle`] does when ticks are missed, i.e. when it is
+/// ticked so late that one or more of the following ticks are already due, for
+/// instance because the task ticking it was busy, or because the browser
+/// throttled timers of a background tab.
+///
+/// Given an interval of 100ms started at 0ms, which is ticked for the first
+/// time only at 350ms, the ticks are scheduled as follows:
+///
+/// | Behavior | Ticks (scheduled at, in ms)         |
+/// |----------|-------------------------------------|
+/// | `Burst`  | 100, 200, 300, 400, 500, ...        |
+/// | `Delay`  | 100, 450, 550, 650, ...             |
+/// | `Skip`   | 100, 400, 500, 600, ...             |
+///
+/// With `Burst`, the ticks at 200 and 300 complete immediately after the first
+/// one, unless the buffer policy of the interval drops some of them (see
+/// [`IntervalHandle::set_buffer_policy`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum MissedTickBehavior {
+    /// Completes missed ticks as soon as possible, one after the other, until
+    /// the interval catches up with its schedule. The schedule is kept.
+    #[default]
+    Burst,
+    /// Schedules the next tick a full period after the late tick actually
+    /// completed, shifting the schedule by the delay.
+    Delay,
+    /// Drops missed ticks, scheduling the next tick to the next instant of the
+    /// original schedule that is still in the future.
+    Skip,
+}
+
+impl MissedTickBehavior {
+    /// Computes the deadline of the tick after the given one, which completed
+    /// at `now`.
+    fn next_deadline(
+        self,
+        scheduled: Instant,
+        now: Instant,
+        period: Duration,
+    ) -> Instant {
+        let next = scheduled + period;
+        if next >= now {
+            return next;
+        }
+        match self {
+            Self::Burst => next,
+            Self::Delay => now + period,
+            Self::Skip => {
+                let late = now.duration_since(scheduled).as_nanos();
+                let missed = late / period.as_nanos().max(1);
+                scheduled + period * periods(missed + 1)
+            },
+        }
+    }
+}
+
+/// Converts a count of periods to a multiplier of a [`Duration`], saturating.
+fn periods(count: u128) -> u32 {
+    u32::try_from(count).unwrap_or(u32::MAX)
+}
+
+/// Whether the given deadline is reached at `now`. Deadlines within the
+/// rounding of `now` to nanoseconds are reached.
+fn is_reached(deadline: Instant, now: Instant) -> bool {
+    deadline.saturating_duration_since(now) == Duration::ZERO
+}
+
+/// A handle to an [`interval`] call. An interval can be waited through
+/// `.tick().await`, or it can be cancelled when the handle is dropped without
+/// the interval completing.
+///
+/// Ticks are scheduled against [`Instant`] deadlines, one period after the
+/// other, so delays in completing a tick do not accumulate. Ticks that were
+/// missed are handled according to the interval's [`MissedTickBehavior`], and
+/// bounded by the policy of its buffer of missed ticks.
+pub struct IntervalHandle {
+    period: Duration,
+    behavior: Cell<MissedTickBehavior>,
+    next: Cell<Instant>,
+    missed: RefCell<Buffer<Instant>>,
+    timeout: RefCell<TimeoutHandle>,
+}
+
+impl IntervalHandle {
+    fn new(period: Duration) -> Self {
+        let next = Instant::now() + period;
+        Self {
+            period,
+            behavior: Cell::new(MissedTickBehavior::default()),
+            next: Cell::new(next),
+            missed: RefCell::new(Buffer::new(BufferPolicy::Unbounded)),
+            timeout: RefCell::new(sleep_until(next)),
+        }
+    }
+
+    /// Ticks for the next interval, yielding the instant at which the tick was
+    /// scheduled. This is an asynchronous function.
+    pub fn tick<'this>(&'this self) -> IntervalTick<'this> {
+        IntervalTick { handle: self }
+    }
+
+    /// The period between ticks of this interval.
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+
+    /// The instant at which the next tick is scheduled.
+    pub fn next_tick(&self) -> Instant {
+        self.missed.borrow().front().copied().unwrap_or(self.next.get())
+    }
+
+    /// The behavior of this interval when ticks are missed.
+    pub fn missed_tick_behavior(&self) -> MissedTickBehavior {
+        self.behavior.get()
+    }
+
+    /// Changes the behavior of this interval when ticks are missed.
+    pub fn set_missed_tick_behavior(&self, behavior: MissedTickBehavior) {
+        self.behavior.set(behavior);
+    }
+
+    /// Sets the behavior of this interval when ticks are missed, returning the
+    /// handle back.
+    pub fn with_missed_tick_behavior(
+        self,
+        behavior: MissedTickBehavior,
+    ) -> Self {
+        self.set_missed_tick_behavior(behavior);
+        self
+    }
+
+    /// Changes the policy of the buffer of missed ticks of this interval. With
+    /// [`MissedTickBehavior::Burst`], the buffer bounds how many missed ticks
+    /// are caught up with, the excess being dropped according to the policy.
+    /// With the other behaviors, at most one tick is due at a time, so no tick
+    /// is dropped. The default policy is [`BufferPolicy::Unbounded`], so that
+    /// every missed tick is caught up with.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the policy has a capacity of zero.
+    pub fn set_buffer_policy(&self, policy: BufferPolicy) {
+        self.missed.borrow_mut().set_policy(policy);
+    }
+
+    /// Sets the policy of the buffer of missed ticks of this interval,
+    /// returning the handle back.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the policy has a capacity of zero.
+    pub fn buffered(self, policy: BufferPolicy) -> Self {
+        self.set_buffer_policy(policy);
+        self
+    }
+
+    /// Returns how many ticks were dropped so far because of the policy of
+    /// the buffer.
+    pub fn dropped(&self) -> u64 {
+        self.missed.borrow().dropped()
+    }
+
+    /// Moves the ticks that are due into the buffer of missed ticks, dropping
+    /// the ones in excess. An unbounded buffer is left empty, as the schedule
+    /// already keeps track of every tick.
+    fn buffer_missed(&self, missed: &mut Buffer<Instant>, now: Instant) {
+        let first = self.next.get();
+        let policy = missed.policy();
+        let Some(capacity) = policy.capacity() else { return };
+        if !is_reached(first, now) {
+            return;
+        }
+        let late = now.saturating_duration_since(first).as_nanos();
+        let due = late / self.period.as_nanos().max(1) + 1;
+        let kept = due.min(capacity as u128);
+        let skipped = match policy {
+            BufferPolicy::DropNewest(_) => 0,
+            _ => due - kept,
+        };
+        for index in skipped .. skipped + kept {
+            missed.push(first + self.period * periods(index));
+        }
+        missed.count_dropped(u64::try_from(due - kept).unwrap_or(u64::MAX));
+        self.next.set(first + self.period * periods(due));
+    }
+
+    fn poll_tick(&self, ctx: &mut task::Context<'_>) -> task::Poll<Instant> {
+        {
+            let mut missed = self.missed.borrow_mut();
+            if self.behavior.get() == MissedTickBehavior::Burst {
+                self.buffer_missed(&mut missed, Instant::now());
+            }
+            if let Some(scheduled) = missed.pop() {
+                return task::Poll::Ready(scheduled);
+            }
+        }
+        let scheduled = self.next.get();
+        if !is_reached(scheduled, Instant::now()) {
+            let mut timeout = self.timeout.borrow_mut();
+            if timeout.deadline() != scheduled {
+                timeout.reset(scheduled);
+            }
+            if Pin::new(&mut *timeout).poll(ctx).is_pending() {
+                return task::Poll::Pending;
+            }
+        }
+        let now = Instant::now();
+        let next =
+            self.behavior.get().next_deadline(scheduled, now, self.period);
+        self.next.set(next);
+        task::Poll::Ready(scheduled)
+    }
+}
+
+/// Yields `()` for every tick. Use [`IntervalHandle::tick`] to get the instant
+/// at which ticks were scheduled.
+#[cfg(feature = "stream")]
+impl Stream for IntervalHandle {
+    type Item = ();
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        ctx: &mut task::Context<'_>,
+    ) -> task::Poll<Option<Self::Item>> {
+        self.poll_tick(ctx).map(|_| Some(()))
+    }
+}
+
+/// A single interval tick that can be awaited.
+pub struct IntervalTick<'handle> {
+    handle: &'handle IntervalHandle,
+}
+
+impl<'handle> Future for IntervalTick<'handle> {
+    type Output = Instant;
+
+    fn poll(
+        self: Pin<&mut Self>,
+        ctx: &mut task::Context<'_>,
+    ) -> task::Poll<Self::Output> {
+        self.handle.poll_tick(ctx)
+    }
+}
+
+/// Creates a handle that produces [`Future`]s that, when awaited, are always
+/// completed approximately with the same interval between them, they "tick",
+/// given by a `duration`. Each tick yields the instant at which it was
+/// scheduled, the first one being one `duration` after the interval was
+/// created.
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use webio::time::{interval, Instant};
+///
+/// # use webio::task;
+/// # fn main() {
+/// # task::detach(async {
+/// let time = Duration::from_millis(100);
+/// let then = Instant::now();
+/// let handle = interval(time);
+///
+/// let scheduled = handle.tick().await;
+/// assert!(scheduled >= then + time);
+/// let passed = then.elapsed();
+/// assert!(passed >= time);
+/// assert!(passed < time + Duration::from_millis(50));
+///
+/// let scheduled = handle.tick().await;
+/// assert_eq!(scheduled, handle.next_tick() - time);
+/// let passed = then.elapsed();
+/// assert!(passed >= time * 2);
+/// assert!(passed < time * 2 + Duration::from_millis(50));
+/// # });
+/// # }
+/// ```
+pub fn interval(duration: Duration) -> IntervalHandle {
+    IntervalHandle::new(duration)
+}
@@ -0,0 +1,218 @@
+//! This module implements a mock clock, for tests that depend on time. While
+//! time is paused, [`Instant::now`] stands still, and timers set by
+//! [`timeout`](super::timeout) and [`interval`](super::interval) only expire
+//! when time is explicitly moved forward through [`advance`], so they resolve
+//! instantly and deterministically.
+//!
+//! Only timers set while time is paused run on the mock clock. Timers set
+//! before pausing keep running on the real clock.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use std::time::Duration;
+//! use webio::{
+//!     task,
+//!     time::{mock, timeout, Instant},
+//! };
+//!
+//! # fn main() {
+//! # task::detach(async {
+//! mock::pause();
+//! let then = Instant::now();
+//! let handle = task::spawn(timeout(Duration::from_secs(60)));
+//! mock::advance(Duration::from_secs(60)).await;
+//! handle.await.unwrap();
+//! assert_eq!(then.elapsed(), Duration::from_secs(60));
+//! mock::resume();
+//! # });
+//! # }
+//! ```
+
+use super::{timer::RealTimer, Instant};
+use std::{cell::RefCell, collections::HashMap, time::Duration};
+
+struct Entry {
+    deadline: Instant,
+    callback: Box<dyn FnOnce()>,
+}
+
+#[derive(Default)]
+struct Clock {
+    paused_at: Option<Instant>,
+    /// Milliseconds to add to the real clock to get the mock clock, which can
+    /// be negative when less time was advanced than really passed while
+    /// paused.
+    offset_millis: f64,
+    next_id: u64,
+    entries: HashMap<u64, Entry>,
+    driver: Option<RealTimer>,
+}
+
+impl Clock {
+    fn now(&self, real_now: Instant) -> Instant {
+        self.paused_at.unwrap_or_else(|| {
+            let millis = real_now.as_millis_f64() + self.offset_millis;
+            Instant::from_dom_high_res_timestamp(millis)
+        })
+    }
+
+    fn next_deadline(&self) -> Option<(Instant, u64)> {
+        self.entries.iter().map(|(id, entry)| (entry.deadline, *id)).min()
+    }
+}
+
+thread_local! {
+    static CLOCK: RefCell<Clock> = RefCell::new(Clock::default());
+}
+
+/// Adjusts a measurement of the real clock to the mock clock.
+pub(crate) fn adjust(real_now: Instant) -> Instant {
+    CLOCK.with(|clock| clock.borrow().now(real_now))
+}
+
+/// A timer running on the mock clock, cleared when dropped.
+#[derive(Debug)]
+pub(crate) struct MockTimer {
+    id: u64,
+}
+
+impl MockTimer {
+    pub(crate) fn new<F>(delay: Duration, callback: F) -> Self
+    where
+        F: FnOnce() + 'static,
+    {
+        let real_now = Instant::real_now();
+        CLOCK.with(|clock| {
+            let mut clock = clock.borrow_mut();
+            let now = clock.now(real_now);
+            let id = clock.next_id;
+            clock.next_id += 1;
+            let entry =
+                Entry { deadline: now + delay, callback: Box::new(callback) };
+            clock.entries.insert(id, entry);
+            Self { id }
+        })
+    }
+}
+
+impl Drop for MockTimer {
+    fn drop(&mut self) {
+        let _ = CLOCK.try_with(|clock| {
+            clock.borrow_mut().entries.remove(&self.id);
+        });
+    }
+}
+
+/// Pauses time. Until [`resume`] is called, [`Instant::now`] stands still,
+/// and new timers only expire when time is moved forward through [`advance`].
+/// Pausing while already paused has no effect.
+pub fn pause() {
+    let real_now = Instant::real_now();
+    CLOCK.with(|clock| {
+        let mut clock = clock.borrow_mut();
+        if clock.paused_at.is_none() {
+            clock.paused_at = Some(clock.now(real_now));
+            clock.driver = None;
+        }
+    });
+}
+
+/// Resumes time, which then moves forward along with the real clock, starting
+/// from where the mock clock stood, i.e. [`Instant::now`] never goes backwards.
+/// Timers set while paused keep their deadlines on the mock clock. Resuming
+/// while not paused has no effect.
+pub fn resume() {
+    let real_now = Instant::real_now();
+    CLOCK.with(|clock| {
+        let mut clock = clock.borrow_mut();
+        if let Some(mock_now) = clock.paused_at.take() {
+            clock.offset_millis =
+                mock_now.as_millis_f64() - real_now.as_millis_f64();
+        }
+    });
+    schedule_driver();
+}
+
+/// Returns whether time is paused.
+pub fn is_paused() -> bool {
+    CLOCK.with(|clock| clock.borrow().paused_at.is_some())
+}
+
+/// Moves paused time forward by the given duration, firing timers whose
+/// deadlines are reached, in the order of their deadlines. Between timers, the
+/// tasks woken by them get to run, so that timers they set are fired as well if
+/// their deadlines are reached.
+///
+/// # Panics
+///
+/// Panics if time is not paused.
+pub async fn advance(duration: Duration) {
+    let target = match CLOCK.with(|clock| clock.borrow().paused_at) {
+        Some(paused_at) => paused_at + duration,
+        None => panic!("time can only be advanced while paused"),
+    };
+    loop {
+        settle().await;
+        let next = CLOCK.with(|clock| {
+            let mut clock = clock.borrow_mut();
+            let (deadline, id) = clock
+                .next_deadline()
+                .filter(|(deadline, _)| *deadline <= target)?;
+            clock.paused_at = Some(deadline);
+            Some(id)
+        });
+        match next {
+            Some(id) => fire(id),
+            None => break,
+        }
+    }
+    CLOCK.with(|clock| clock.borrow_mut().paused_at = Some(target));
+    settle().await;
+}
+
+/// Waits until the tasks that are ready have run, by waiting for a macrotask
+/// on the real clock.
+async fn settle() {
+    super::real_timeout(Duration::ZERO).await;
+}
+
+/// Fires the given timer, unless it was cleared.
+fn fire(id: u64) {
+    let entry = CLOCK.with(|clock| clock.borrow_mut().entries.remove(&id));
+    if let Some(entry) = entry {
+        (entry.callback)();
+    }
+}
+
+/// While not paused, timers set while paused are driven by a timer on the real
+/// clock for the earliest deadline.
+fn schedule_driver() {
+    let real_now = Instant::real_now();
+    let delay = CLOCK.with(|clock| {
+        let clock = clock.borrow();
+        if clock.paused_at.is_some() {
+            return None;
+        }
+        let (deadline, _) = clock.next_deadline()?;
+        Some(deadline.saturating_duration_since(clock.now(real_now)))
+    });
+    let driver = delay.map(|delay| {
+        RealTimer::timeout(delay, || {
+            let real_now = Instant::real_now();
+            let due = CLOCK.with(|clock| {
+                let clock = clock.borrow();
+                let now = clock.now(real_now);
+                clock
+                    .next_deadline()
+                    .filter(|(deadline, _)| *deadline <= now)
+                    .map(|(_, id)| id)
+            });
+            if let Some(id) = due {
+                fire(id);
+            }
+            schedule_driver();
+        })
+    });
+    CLOCK.with(|clock| clock.borrow_mut().driver = driver);
+}
@@ -0,0 +1,456 @@
+//! This module wraps the User Timing and Performance Timeline APIs, i.e.
+//! `performance.mark`, `performance.measure` and `PerformanceObserver`, so
+//! that timings show up in the performance panel of the browser's developer
+//! tools, and can be collected programmatically through an [`Observer`].
+//!
+//! Marks and measures are timed with [`Instant`], and so they follow the
+//! [mock clock](super::mock) when time is paused. On native targets, where
+//! there is no performance panel, entries are only delivered to observers.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use webio::time::perf;
+//!
+//! # fn main() {
+//! # webio::task::detach(async {
+//! # fn render() {}
+//! let observer = perf::observe();
+//! {
+//!     let _span = perf::Span::new("render");
+//!     render();
+//! }
+//! let mark = observer.next_entry().await;
+//! assert_eq!(mark.kind(), perf::EntryKind::Mark);
+//! let measure = observer.next_entry().await;
+//! assert_eq!(measure.kind(), perf::EntryKind::Measure);
+//! println!("render took {:?}", measure.duration());
+//! # });
+//! # }
+//! ```
+
+use super::Instant;
+use crate::callback;
+use std::{future::Future, pin::Pin, task, time::Duration};
+
+#[cfg(target_arch = "wasm32")]
+use js_sys::{Array, Function, Object, Reflect};
+#[cfg(target_arch = "wasm32")]
+use std::fmt;
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::{closure::Closure, prelude::wasm_bindgen, JsCast, JsValue};
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::{cell::RefCell, collections::BTreeMap};
+
+#[cfg(feature = "stream")]
+use futures::stream::Stream;
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(extends = ::js_sys::Object, js_name = Performance)]
+    type Performance;
+
+    #[wasm_bindgen(method, structural, catch)]
+    fn mark(
+        this: &Performance,
+        name: &str,
+        options: &JsValue,
+    ) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(method, structural, catch)]
+    fn measure(
+        this: &Performance,
+        name: &str,
+        options: &JsValue,
+    ) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(extends = ::js_sys::Object, js_name = PerformanceObserver)]
+    type PerformanceObserver;
+
+    #[wasm_bindgen(constructor, js_class = PerformanceObserver)]
+    fn new(callback: &Function) -> PerformanceObserver;
+
+    #[wasm_bindgen(method, structural)]
+    fn observe(this: &PerformanceObserver, options: &JsValue);
+
+    #[wasm_bindgen(method, structural)]
+    fn disconnect(this: &PerformanceObserver);
+
+    #[wasm_bindgen(extends = ::js_sys::Object, js_name = PerformanceObserverEntryList)]
+    type PerformanceObserverEntryList;
+
+    #[wasm_bindgen(method, structural, js_name = getEntries)]
+    fn get_entries(this: &PerformanceObserverEntryList) -> Array;
+
+    #[wasm_bindgen(extends = ::js_sys::Object, js_name = PerformanceEntry)]
+    type PerformanceEntry;
+
+    #[wasm_bindgen(method, structural, getter)]
+    fn name(this: &PerformanceEntry) -> String;
+
+    #[wasm_bindgen(method, structural, getter, js_name = entryType)]
+    fn entry_type(this: &PerformanceEntry) -> String;
+
+    #[wasm_bindgen(method, structural, getter, js_name = startTime)]
+    fn start_time(this: &PerformanceEntry) -> f64;
+
+    #[wasm_bindgen(method, structural, getter)]
+    fn duration(this: &PerformanceEntry) -> f64;
+}
+
+#[cfg(target_arch = "wasm32")]
+fn performance() -> Performance {
+    Reflect::get(&js_sys::global(), &JsValue::from_str("performance"))
+        .unwrap()
+        .unchecked_into()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn options(entries: &[(&str, f64)]) -> JsValue {
+    let options = Object::new();
+    for (key, value) in entries {
+        Reflect::set(&options, &JsValue::from_str(key), &JsValue::from(*value))
+            .unwrap();
+    }
+    options.into()
+}
+
+/// The kind of a performance [`Entry`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EntryKind {
+    /// A single point in time, recorded by [`mark`].
+    Mark,
+    /// A span of time, recorded by [`measure`] or when a [`Span`] ends.
+    Measure,
+}
+
+/// A performance entry, i.e. a mark or a measure.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Entry {
+    name: String,
+    kind: EntryKind,
+    start: Instant,
+    duration: Duration,
+}
+
+impl Entry {
+    /// The name given to this entry.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Whether this entry is a mark or a measure.
+    pub fn kind(&self) -> EntryKind {
+        self.kind
+    }
+
+    /// The instant at which this entry starts.
+    pub fn start(&self) -> Instant {
+        self.start
+    }
+
+    /// The duration of this entry, always zero for marks.
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// The instant at which this entry ends, the same as its start for marks.
+    pub fn end(&self) -> Instant {
+        self.start + self.duration
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn from_js(js_entry: &PerformanceEntry) -> Option<Self> {
+        let kind = match js_entry.entry_type().as_str() {
+            "mark" => EntryKind::Mark,
+            "measure" => EntryKind::Measure,
+            _ => return None,
+        };
+        let millis = js_entry.duration().max(0.0);
+        Some(Self {
+            name: js_entry.name(),
+            kind,
+            start: Instant::from_dom_high_res_timestamp(js_entry.start_time()),
+            duration: Duration::from_nanos(
+                (millis * 1_000_000.0).round() as u64
+            ),
+        })
+    }
+}
+
+/// Records a mark with the given name at this right moment, returning the
+/// recorded entry.
+///
+/// # Panics
+///
+/// Panics if the browser rejects the name, e.g. because it is reserved, such as
+/// `"navigationStart"`.
+pub fn mark<S>(name: S) -> Entry
+where
+    S: Into<String>,
+{
+    let entry = Entry {
+        name: name.into(),
+        kind: EntryKind::Mark,
+        start: Instant::now(),
+        duration: Duration::ZERO,
+    };
+    record(&entry);
+    entry
+}
+
+/// Records a measure with the given name, spanning from `start` to `end`,
+/// returning the recorded entry. If `end` is earlier than `start`, the measure
+/// is zero-sized.
+///
+/// # Panics
+///
+/// Panics if the browser rejects the name.
+pub fn measure<S>(name: S, start: Instant, end: Instant) -> Entry
+where
+    S: Into<String>,
+{
+    let entry = Entry {
+        name: name.into(),
+        kind: EntryKind::Measure,
+        start,
+        duration: end.saturating_duration_since(start),
+    };
+    record(&entry);
+    entry
+}
+
+#[cfg(target_arch = "wasm32")]
+fn record(entry: &Entry) {
+    let start = entry.start.as_millis_f64();
+    let result = match entry.kind {
+        EntryKind::Mark => {
+            performance().mark(&entry.name, &options(&[("startTime", start)]))
+        },
+        EntryKind::Measure => {
+            let end = entry.end().as_millis_f64();
+            let options = options(&[("start", start), ("end", end)]);
+            performance().measure(&entry.name, &options)
+        },
+    };
+    if let Err(error) = result {
+        panic!("performance entry {:?} rejected: {:?}", entry.name, error);
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn record(entry: &Entry) {
+    OBSERVERS.with(|observers| {
+        let mut observers = observers.borrow_mut();
+        for callback in observers.callbacks.values_mut() {
+            callback(entry.clone());
+        }
+    });
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Default)]
+struct Observers {
+    next_id: u64,
+    callbacks: BTreeMap<u64, callback::multi::SyncCbHandler<'static, Entry>>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+thread_local! {
+    static OBSERVERS: RefCell<Observers> = RefCell::new(Observers::default());
+}
+
+/// A span of time, recorded as a measure in the performance timeline. A mark
+/// is recorded when the span is created, and a measure is recorded when it
+/// ends, either through [`Span::end`] or when it is dropped. Both entries are
+/// given the span's name.
+///
+/// # Examples
+///
+/// ```no_run
+/// use webio::time::perf::Span;
+///
+/// # fn main() {
+/// # webio::task::detach(async {
+/// # async fn fetch_profile() {}
+/// let span = Span::new("fetch profile");
+/// fetch_profile().await;
+/// let measure = span.end();
+/// println!("fetching the profile took {:?}", measure.duration());
+/// # });
+/// # }
+/// ```
+#[derive(Debug)]
+#[must_use = "the span ends as soon as it is dropped"]
+pub struct Span {
+    name: String,
+    start: Instant,
+    ended: bool,
+}
+
+impl Span {
+    /// Starts a span with the given name, recording a mark.
+    pub fn new<S>(name: S) -> Self
+    where
+        S: Into<String>,
+    {
+        let start = mark(name);
+        Self { name: start.name, start: start.start, ended: false }
+    }
+
+    /// The name of this span.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The instant at which this span started.
+    pub fn start(&self) -> Instant {
+        self.start
+    }
+
+    /// Ends this span, recording and returning its measure.
+    pub fn end(mut self) -> Entry {
+        self.finish()
+    }
+
+    fn finish(&mut self) -> Entry {
+        self.ended = true;
+        measure(std::mem::take(&mut self.name), self.start, Instant::now())
+    }
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        if !self.ended {
+            self.finish();
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+struct Registration {
+    observer: PerformanceObserver,
+    _closure: JsValue,
+}
+
+#[cfg(target_arch = "wasm32")]
+impl fmt::Debug for Registration {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        fmtr.pad("Registration { .. }")
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Drop for Registration {
+    fn drop(&mut self) {
+        self.observer.disconnect();
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug)]
+struct Registration {
+    id: u64,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Drop for Registration {
+    fn drop(&mut self) {
+        let _ = OBSERVERS.try_with(|observers| {
+            observers.borrow_mut().callbacks.remove(&self.id);
+        });
+    }
+}
+
+/// An observer of marks and measures, created by [`observe`]. Entries can be
+/// waited through `.next_entry().await`, and observing stops when the
+/// observer is dropped. Entries recorded while no entry is being waited are
+/// buffered.
+#[derive(Debug)]
+pub struct Observer {
+    listener: callback::multi::Listener<Entry>,
+    _registration: Registration,
+}
+
+impl Observer {
+    /// Waits for the next entry. This is an asynchronous function.
+    pub fn next_entry<'this>(&'this self) -> NextEntry<'this> {
+        NextEntry { listener: self.listener.listen_next() }
+    }
+}
+
+#[cfg(feature = "stream")]
+impl Stream for Observer {
+    type Item = Entry;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        ctx: &mut task::Context<'_>,
+    ) -> task::Poll<Option<Self::Item>> {
+        Pin::new(&mut self.listener).poll_next(ctx)
+    }
+}
+
+/// A single entry of an [`Observer`] that can be awaited.
+#[derive(Debug)]
+pub struct NextEntry<'observer> {
+    listener: callback::multi::ListenNext<'observer, Entry>,
+}
+
+impl<'observer> Future for NextEntry<'observer> {
+    type Output = Entry;
+
+    fn poll(
+        mut self: Pin<&mut Self>,
+        ctx: &mut task::Context<'_>,
+    ) -> task::Poll<Self::Output> {
+        Pin::new(&mut self.listener).poll(ctx).map(Result::unwrap)
+    }
+}
+
+/// Starts observing marks and measures recorded from now on, including the
+/// ones recorded by other code, such as JS libraries.
+pub fn observe() -> Observer {
+    let register = callback::multi::SyncRegister::new(register_observer)
+        .buffered(callback::BufferPolicy::Unbounded);
+    let (registration, listener) = register.listen_returning(|entry| entry);
+    Observer { listener, _registration: registration }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn register_observer(
+    mut callback: callback::multi::SyncCbHandler<'static, Entry>,
+) -> Registration {
+    let closure =
+        Closure::wrap(Box::new(move |list: PerformanceObserverEntryList| {
+            for js_entry in list.get_entries().iter() {
+                if let Some(entry) = Entry::from_js(js_entry.unchecked_ref()) {
+                    callback(entry);
+                }
+            }
+        })
+            as Box<dyn FnMut(PerformanceObserverEntryList)>)
+        .into_js_value();
+    let observer = PerformanceObserver::new(closure.unchecked_ref());
+    let entry_types = Array::of2(&"mark".into(), &"measure".into());
+    let options = Object::new();
+    Reflect::set(&options, &JsValue::from_str("entryTypes"), &entry_types)
+        .unwrap();
+    observer.observe(&options);
+    Registration { observer, _closure: closure }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn register_observer(
+    callback: callback::multi::SyncCbHandler<'static, Entry>,
+) -> Registration {
+    OBSERVERS.with(|observers| {
+        let mut observers = observers.borrow_mut();
+        let id = observers.next_id;
+        observers.next_id += 1;
+        observers.callbacks.insert(id, callback);
+        Registration { id }
+    })
+}
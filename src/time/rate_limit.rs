@@ -0,0 +1,250 @@
+//! Implementation of debouncing and throttling of sequences of items, such as
+//! event occurences.
+
+use super::{sleep_until, timeout, Instant, TimeoutHandle};
+use std::{future::Future, pin::Pin, task, time::Duration};
+
+#[cfg(feature = "stream")]
+use futures::stream::Stream;
+#[cfg(feature = "stream")]
+use pin_project::pin_project;
+
+/// State of debouncing: only the last item of a burst is yielded, once no item
+/// arrives for a given duration.
+pub(crate) struct Debouncer<T> {
+    duration: Duration,
+    pending: Option<T>,
+    deadline: Instant,
+    quiet: Option<TimeoutHandle>,
+    finished: bool,
+}
+
+impl<T> Debouncer<T> {
+    pub(crate) fn new(duration: Duration) -> Self {
+        Self {
+            duration,
+            pending: None,
+            deadline: Instant::now(),
+            quiet: None,
+            finished: false,
+        }
+    }
+
+    /// Polls for the next debounced item, pulling items through `poll_next`.
+    /// When the source finishes, the pending item is yielded right away.
+    pub(crate) fn poll<F>(
+        &mut self,
+        ctx: &mut task::Context<'_>,
+        mut poll_next: F,
+    ) -> task::Poll<Option<T>>
+    where
+        F: FnMut(&mut task::Context<'_>) -> task::Poll<Option<T>>,
+    {
+        let mut received = false;
+        while !self.finished {
+            match poll_next(ctx) {
+                task::Poll::Ready(Some(item)) => {
+                    self.pending = Some(item);
+                    received = true;
+                },
+                task::Poll::Ready(None) => self.finished = true,
+                task::Poll::Pending => break,
+            }
+        }
+
+        if self.pending.is_none() {
+            if self.finished {
+                return task::Poll::Ready(None);
+            }
+            return task::Poll::Pending;
+        }
+
+        if !self.finished {
+            if received {
+                self.deadline = Instant::now() + self.duration;
+            }
+            let deadline = self.deadline;
+            // Rather than being rescheduled at every item, the timer is only
+            // moved to the latest deadline once it expires.
+            let quiet = self.quiet.get_or_insert_with(|| sleep_until(deadline));
+            loop {
+                if Pin::new(&mut *quiet).poll(ctx).is_pending() {
+                    return task::Poll::Pending;
+                }
+                if quiet.deadline() >= deadline {
+                    break;
+                }
+                quiet.reset(deadline);
+            }
+            // The timer expired, the next burst needs a new one.
+            self.quiet = None;
+        }
+
+        task::Poll::Ready(self.pending.take())
+    }
+}
+
+/// State of throttling: an item is yielded right away, and then the items
+/// arriving during a window of a given duration are dropped.
+pub(crate) struct Throttler {
+    duration: Duration,
+    window: Option<TimeoutHandle>,
+    open: bool,
+}
+
+impl Throttler {
+    pub(crate) fn new(duration: Duration) -> Self {
+        Self { duration, window: None, open: false }
+    }
+
+    /// Polls for the next throttled item, pulling items through `poll_next`.
+    pub(crate) fn poll<T, F>(
+        &mut self,
+        ctx: &mut task::Context<'_>,
+        mut poll_next: F,
+    ) -> task::Poll<Option<T>>
+    where
+        F: FnMut(&mut task::Context<'_>) -> task::Poll<Option<T>>,
+    {
+        if self.open {
+            let expired = match &mut self.window {
+                Some(window) => Pin::new(window).poll(ctx).is_ready(),
+                None => true,
+            };
+            if !expired {
+                // Items arriving while the window is still open are dropped.
+                loop {
+                    match poll_next(ctx) {
+                        task::Poll::Ready(Some(_)) => (),
+                        task::Poll::Ready(None) => {
+                            return task::Poll::Ready(None)
+                        },
+                        task::Poll::Pending => return task::Poll::Pending,
+                    }
+                }
+            }
+            self.open = false;
+        }
+
+        let item = match poll_next(ctx) {
+            task::Poll::Ready(Some(item)) => item,
+            poll => return poll,
+        };
+        // The window is only reset once it expired, so no armed timer is
+        // replaced.
+        let deadline = Instant::now() + self.duration;
+        match &mut self.window {
+            Some(window) => window.reset(deadline),
+            None => self.window = Some(timeout(self.duration)),
+        }
+        self.open = true;
+        task::Poll::Ready(Some(item))
+    }
+}
+
+/// A stream debounced by [`TimeStreamExt::debounce`].
+#[cfg(feature = "stream")]
+#[cfg_attr(feature = "feature-doc-cfg", doc(cfg(feature = "stream")))]
+#[pin_project]
+pub struct Debounce<S>
+where
+    S: Stream,
+{
+    #[pin]
+    stream: S,
+    debouncer: Debouncer<S::Item>,
+}
+
+#[cfg(feature = "stream")]
+impl<S> Stream for Debounce<S>
+where
+    S: Stream,
+{
+    type Item = S::Item;
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        ctx: &mut task::Context<'_>,
+    ) -> task::Poll<Option<Self::Item>> {
+        let mut this = self.project();
+        this.debouncer.poll(ctx, |ctx| this.stream.as_mut().poll_next(ctx))
+    }
+}
+
+/// A stream throttled by [`TimeStreamExt::throttle`].
+#[cfg(feature = "stream")]
+#[cfg_attr(feature = "feature-doc-cfg", doc(cfg(feature = "stream")))]
+#[pin_project]
+pub struct Throttle<S> {
+    #[pin]
+    stream: S,
+    throttler: Throttler,
+}
+
+#[cfg(feature = "stream")]
+impl<S> Stream for Throttle<S>
+where
+    S: Stream,
+{
+    type Item = S::Item;
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        ctx: &mut task::Context<'_>,
+    ) -> task::Poll<Option<Self::Item>> {
+        let mut this = self.project();
+        this.throttler.poll(ctx, |ctx| this.stream.as_mut().poll_next(ctx))
+    }
+}
+
+/// Extension trait debouncing and throttling any [`Stream`].
+#[cfg(feature = "stream")]
+#[cfg_attr(feature = "feature-doc-cfg", doc(cfg(feature = "stream")))]
+pub trait TimeStreamExt: Stream + Sized {
+    /// Debounces this stream: after an item arrives, waits until no other item
+    /// arrives for the given duration, and then yields only the last item.
+    /// When this stream ends, the last pending item is yielded right away.
+    ///
+    /// ```no_run
+    /// use futures::stream::{self, StreamExt};
+    /// use std::time::Duration;
+    /// use webio::time::TimeStreamExt;
+    ///
+    /// # use webio::task;
+    /// # fn main() {
+    /// # task::detach(async {
+    /// let keystrokes = stream::iter(["w", "we", "web"]);
+    /// let mut searches = keystrokes.debounce(Duration::from_millis(300));
+    /// assert_eq!(searches.next().await, Some("web"));
+    /// # });
+    /// # }
+    /// ```
+    fn debounce(self, duration: Duration) -> Debounce<Self> {
+        Debounce { stream: self, debouncer: Debouncer::new(duration) }
+    }
+
+    /// Throttles this stream: yields an item right away, and then drops the
+    /// items arriving during the given duration.
+    ///
+    /// ```no_run
+    /// use futures::stream::{self, StreamExt};
+    /// use std::time::Duration;
+    /// use webio::time::TimeStreamExt;
+    ///
+    /// # use webio::task;
+    /// # fn main() {
+    /// # task::detach(async {
+    /// let scrolls = stream::iter([10, 20, 30]);
+    /// let mut updates = scrolls.throttle(Duration::from_millis(100));
+    /// assert_eq!(updates.next().await, Some(10));
+    /// assert_eq!(updates.next().await, None);
+    /// # });
+    /// # }
+    /// ```
+    fn throttle(self, duration: Duration) -> Throttle<Self> {
+        Throttle { stream: self, throttler: Throttler::new(duration) }
+    }
+}
+
+#[cfg(feature = "stream")]
+impl<S> TimeStreamExt for S where S: Stream {}
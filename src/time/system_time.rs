@@ -0,0 +1,233 @@
+//! Implementation mimicking [`std::time::SystemTime`].
+
+use std::{
+    error::Error,
+    fmt,
+    ops::{Add, AddAssign, Sub, SubAssign},
+    time::Duration,
+};
+
+#[cold]
+#[inline(never)]
+fn system_time_overflow() -> ! {
+    panic!("overflow when adding or subtracting a duration to a system time")
+}
+
+/// An anchor in time, [`SystemTime::UNIX_EPOCH`], i.e. "1970-01-01 00:00:00
+/// UTC".
+pub const UNIX_EPOCH: SystemTime = SystemTime::UNIX_EPOCH;
+
+/// A measurement of the system clock, i.e. wall-clock time, mimicking
+/// [`std::time::SystemTime`] but for WASM, which is not supported by std's
+/// `SystemTime`. Behind the curtains, this type uses JavaScript's `Date.now()`,
+/// and so it has millisecond precision when measured. On native targets, std's
+/// `SystemTime` is used instead.
+///
+/// Unlike [`Instant`](super::Instant), the system clock is not monotonic, and
+/// it is not affected by the [mock clock](super::mock).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SystemTime {
+    nanos: i128,
+}
+
+impl SystemTime {
+    /// An anchor in time, i.e. "1970-01-01 00:00:00 UTC", from which other
+    /// system times can be measured.
+    pub const UNIX_EPOCH: Self = Self { nanos: 0 };
+
+    /// Gets the system time for this right moment, through JS `Date.now()`.
+    /// On native targets, std's `SystemTime` is used instead.
+    #[cfg(target_arch = "wasm32")]
+    pub fn now() -> Self {
+        Self::from_millis(js_sys::Date::now())
+    }
+
+    /// Gets the system time for this right moment, through JS `Date.now()`.
+    /// On native targets, std's `SystemTime` is used instead.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn now() -> Self {
+        let now = std::time::SystemTime::now();
+        let nanos = match now.duration_since(std::time::UNIX_EPOCH) {
+            Ok(duration) => duration.as_nanos() as i128,
+            Err(error) => -(error.duration().as_nanos() as i128),
+        };
+        Self { nanos }
+    }
+
+    // Whole milliseconds are converted separately from their fraction, since
+    // nanoseconds since the epoch do not fit in the mantissa of an `f64`.
+    #[cfg(target_arch = "wasm32")]
+    fn from_millis(millis: f64) -> Self {
+        let whole = millis.floor();
+        let fraction = ((millis - whole) * 1_000_000.0).round();
+        Self { nanos: whole as i128 * 1_000_000 + fraction as i128 }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn as_millis(self) -> f64 {
+        let whole = self.nanos.div_euclid(1_000_000);
+        let fraction = self.nanos.rem_euclid(1_000_000);
+        whole as f64 + fraction as f64 / 1_000_000.0
+    }
+
+    /// Creates a system time from a JS `Date`. If the date is invalid, i.e.
+    /// its time value is `NaN`, an error is returned.
+    #[cfg(target_arch = "wasm32")]
+    #[cfg_attr(feature = "feature-doc-cfg", doc(cfg(target_arch = "wasm32")))]
+    pub fn from_date(date: &js_sys::Date) -> Result<Self, InvalidDate> {
+        let millis = date.get_time();
+        if millis.is_nan() {
+            Err(InvalidDate)
+        } else {
+            Ok(Self::from_millis(millis))
+        }
+    }
+
+    /// Converts this system time into a JS `Date`, truncated to milliseconds.
+    #[cfg(target_arch = "wasm32")]
+    #[cfg_attr(feature = "feature-doc-cfg", doc(cfg(target_arch = "wasm32")))]
+    pub fn to_date(self) -> js_sys::Date {
+        js_sys::Date::new(&self.as_millis().into())
+    }
+
+    /// Returns the offset of the local timezone from UTC at this system time,
+    /// in minutes, e.g. `60` for UTC+01:00 and `-180` for UTC-03:00. Note that
+    /// the sign is the opposite of JS `Date.getTimezoneOffset()`. The offset
+    /// can differ between system times because of daylight saving time.
+    #[cfg(target_arch = "wasm32")]
+    #[cfg_attr(feature = "feature-doc-cfg", doc(cfg(target_arch = "wasm32")))]
+    pub fn utc_offset_minutes(self) -> i32 {
+        -(self.to_date().get_timezone_offset() as i32)
+    }
+
+    /// Returns the duration of time that passed from an earlier system time
+    /// into this system time. If the `earlier` system time is actually later,
+    /// an error holding how much later it is is returned.
+    pub fn duration_since(
+        self,
+        earlier: Self,
+    ) -> Result<Duration, SystemTimeError> {
+        let nanos = self.nanos - earlier.nanos;
+        let duration = nanos_to_duration(nanos.unsigned_abs());
+        if nanos >= 0 {
+            Ok(duration)
+        } else {
+            Err(SystemTimeError { duration })
+        }
+    }
+
+    /// Returns the duration of time that has passed since this system time.
+    /// Since the system clock is not monotonic, this might fail if the clock
+    /// was adjusted backwards.
+    pub fn elapsed(self) -> Result<Duration, SystemTimeError> {
+        Self::now().duration_since(self)
+    }
+
+    /// Adds a duration of time to this system time. If the resulting system
+    /// time cannot be represented, `None` is returned.
+    pub fn checked_add(self, duration: Duration) -> Option<Self> {
+        let nanos = i128::try_from(duration.as_nanos()).ok()?;
+        Some(Self { nanos: self.nanos.checked_add(nanos)? })
+    }
+
+    /// Subtracts a duration of time from this system time. If the resulting
+    /// system time cannot be represented, `None` is returned.
+    pub fn checked_sub(self, duration: Duration) -> Option<Self> {
+        let nanos = i128::try_from(duration.as_nanos()).ok()?;
+        Some(Self { nanos: self.nanos.checked_sub(nanos)? })
+    }
+}
+
+fn nanos_to_duration(nanos: u128) -> Duration {
+    let secs = (nanos / 1_000_000_000) as u64;
+    Duration::new(secs, (nanos % 1_000_000_000) as u32)
+}
+
+#[cfg(target_arch = "wasm32")]
+impl TryFrom<js_sys::Date> for SystemTime {
+    type Error = InvalidDate;
+
+    fn try_from(date: js_sys::Date) -> Result<Self, Self::Error> {
+        Self::from_date(&date)
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl From<SystemTime> for js_sys::Date {
+    fn from(system_time: SystemTime) -> Self {
+        system_time.to_date()
+    }
+}
+
+impl Add<Duration> for SystemTime {
+    type Output = Self;
+
+    fn add(self, duration: Duration) -> Self::Output {
+        match self.checked_add(duration) {
+            Some(output) => output,
+            None => system_time_overflow(),
+        }
+    }
+}
+
+impl AddAssign<Duration> for SystemTime {
+    fn add_assign(&mut self, duration: Duration) {
+        *self = *self + duration;
+    }
+}
+
+impl Sub<Duration> for SystemTime {
+    type Output = Self;
+
+    fn sub(self, duration: Duration) -> Self::Output {
+        match self.checked_sub(duration) {
+            Some(output) => output,
+            None => system_time_overflow(),
+        }
+    }
+}
+
+impl SubAssign<Duration> for SystemTime {
+    fn sub_assign(&mut self, duration: Duration) {
+        *self = *self - duration;
+    }
+}
+
+/// An error returned by [`SystemTime::duration_since`] and
+/// [`SystemTime::elapsed`] when the earlier system time is actually later.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SystemTimeError {
+    duration: Duration,
+}
+
+impl SystemTimeError {
+    /// Returns how much later the supposedly earlier system time is.
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+}
+
+impl fmt::Display for SystemTimeError {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmtr, "second time provided was later than self")
+    }
+}
+
+impl Error for SystemTimeError {}
+
+/// An error returned by [`SystemTime::from_date`] when the JS `Date` is
+/// invalid.
+#[cfg(target_arch = "wasm32")]
+#[cfg_attr(feature = "feature-doc-cfg", doc(cfg(target_arch = "wasm32")))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidDate;
+
+#[cfg(target_arch = "wasm32")]
+impl fmt::Display for InvalidDate {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmtr, "invalid date")
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Error for InvalidDate {}
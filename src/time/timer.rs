@@ -0,0 +1,106 @@
+//! Implementation of timers over `setTimeout` in WASM, and over
+//! the native backend elsewhere, unless time is paused by the mock clock.
+
+use std::time::Duration;
+
+#[cfg(target_arch = "wasm32")]
+use js_sys::Function;
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::{closure::Closure, prelude::wasm_bindgen, JsCast, JsValue};
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::native;
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_name = "setTimeout")]
+    fn set_timeout(function: &Function, milliseconds: i32) -> JsValue;
+    #[wasm_bindgen(js_name = "clearTimeout")]
+    fn clear_timeout(timeout_id: &JsValue);
+}
+
+/// A running timer of the backend, cleared when dropped. Never affected by the
+/// mock clock.
+#[derive(Debug)]
+pub(crate) struct RealTimer {
+    #[cfg(target_arch = "wasm32")]
+    id: JsValue,
+    /// Freed when the timer is dropped, after the timeout is cleared, whether
+    /// it ran or not.
+    #[cfg(target_arch = "wasm32")]
+    _closure: Closure<dyn FnMut()>,
+    #[cfg(not(target_arch = "wasm32"))]
+    id: native::TimerId,
+}
+
+impl RealTimer {
+    /// Calls the given callback once, after the given delay.
+    #[cfg(target_arch = "wasm32")]
+    pub(crate) fn timeout<F>(delay: Duration, callback: F) -> Self
+    where
+        F: FnOnce() + 'static,
+    {
+        let closure = Closure::once(callback);
+        let milliseconds = super::duration_to_millis(delay);
+        let id = set_timeout(closure.as_ref().unchecked_ref(), milliseconds);
+        Self { id, _closure: closure }
+    }
+
+    /// Calls the given callback once, after the given delay.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn timeout<F>(delay: Duration, callback: F) -> Self
+    where
+        F: FnOnce() + 'static,
+    {
+        Self { id: native::set_timer(delay, callback) }
+    }
+}
+
+impl Drop for RealTimer {
+    fn drop(&mut self) {
+        #[cfg(target_arch = "wasm32")]
+        clear_timeout(&self.id);
+
+        #[cfg(not(target_arch = "wasm32"))]
+        native::clear_timer(self.id);
+    }
+}
+
+/// A running timer, cleared when dropped. Timers set while time is paused (see
+/// [`mock::pause`](super::mock::pause)) run on the mock clock.
+#[derive(Debug)]
+pub(crate) enum Timer {
+    Real {
+        _timer: RealTimer,
+    },
+    #[cfg(feature = "mock")]
+    Mock {
+        _timer: super::mock::MockTimer,
+    },
+}
+
+impl Timer {
+    /// Calls the given callback once, after the given delay.
+    pub(crate) fn timeout<F>(delay: Duration, callback: F) -> Self
+    where
+        F: FnOnce() + 'static,
+    {
+        #[cfg(feature = "mock")]
+        if super::mock::is_paused() {
+            let timer = super::mock::MockTimer::new(delay, callback);
+            return Self::Mock { _timer: timer };
+        }
+
+        Self::real_timeout(delay, callback)
+    }
+
+    /// Calls the given callback once, after the given delay, always on the
+    /// real clock.
+    pub(crate) fn real_timeout<F>(delay: Duration, callback: F) -> Self
+    where
+        F: FnOnce() + 'static,
+    {
+        Self::Real { _timer: RealTimer::timeout(delay, callback) }
+    }
+}
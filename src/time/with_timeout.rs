@@ -0,0 +1,124 @@
+//! Implementation of futures bounded by a timeout.
+
+use super::{timeout, TimeoutHandle};
+use pin_project::pin_project;
+use std::{
+    error::Error,
+    fmt,
+    future::{Future, IntoFuture},
+    pin::Pin,
+    task,
+    time::Duration,
+};
+
+/// An error returned when a future bounded by [`with_timeout`] does not
+/// complete before its timeout expires.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Elapsed;
+
+impl fmt::Display for Elapsed {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmtr, "deadline has elapsed")
+    }
+}
+
+impl Error for Elapsed {}
+
+/// A future bounded by a timeout, created by [`with_timeout`] or
+/// [`TimeoutExt::timeout`]. The inner future is dropped along with this future,
+/// so it is cancelled when the timeout expires.
+#[pin_project]
+pub struct Timeout<F> {
+    #[pin]
+    future: F,
+    #[pin]
+    handle: TimeoutHandle,
+}
+
+impl<F> Timeout<F> {
+    /// Returns a reference to the inner future.
+    pub fn get_ref(&self) -> &F {
+        &self.future
+    }
+
+    /// Returns a mutable reference to the inner future.
+    pub fn get_mut(&mut self) -> &mut F {
+        &mut self.future
+    }
+
+    /// Consumes this future, returning the inner future.
+    pub fn into_inner(self) -> F {
+        self.future
+    }
+}
+
+impl<F> Future for Timeout<F>
+where
+    F: Future,
+{
+    type Output = Result<F::Output, Elapsed>;
+
+    fn poll(
+        self: Pin<&mut Self>,
+        ctx: &mut task::Context<'_>,
+    ) -> task::Poll<Self::Output> {
+        let this = self.project();
+        if let task::Poll::Ready(output) = this.future.poll(ctx) {
+            return task::Poll::Ready(Ok(output));
+        }
+        this.handle.poll(ctx).map(|()| Err(Elapsed))
+    }
+}
+
+/// Runs the given future until it completes, or until some duration of time
+/// has passed, whichever happens first. If the duration passes first, the
+/// future is dropped and [`Elapsed`] is returned.
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use webio::time::{timeout, with_timeout};
+///
+/// # use webio::task;
+/// # fn main() {
+/// # task::detach(async {
+/// # async fn fetch_profile() -> u32 {
+/// #     timeout(Duration::from_secs(10)).await;
+/// #     3
+/// # }
+/// match with_timeout(Duration::from_secs(5), fetch_profile()).await {
+///     Ok(profile) => println!("profile: {}", profile),
+///     Err(elapsed) => println!("fetching profile failed: {}", elapsed),
+/// }
+/// # });
+/// # }
+/// ```
+pub fn with_timeout<F>(duration: Duration, future: F) -> Timeout<F::IntoFuture>
+where
+    F: IntoFuture,
+{
+    Timeout { future: future.into_future(), handle: timeout(duration) }
+}
+
+/// Extension trait bounding any [`Future`] by a timeout.
+pub trait TimeoutExt: Future + Sized {
+    /// Bounds this future by a timeout, just like [`with_timeout`].
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use webio::time::TimeoutExt;
+    ///
+    /// # use webio::task;
+    /// # fn main() {
+    /// # task::detach(async {
+    /// # async fn prompt_user() -> bool { true }
+    /// let confirmed =
+    ///     prompt_user().timeout(Duration::from_secs(30)).await.unwrap_or(false);
+    /// # });
+    /// # }
+    /// ```
+    fn timeout(self, duration: Duration) -> Timeout<Self> {
+        with_timeout(duration, self)
+    }
+}
+
+impl<F> TimeoutExt for F where F: Future {}
@@ -0,0 +1,31 @@
+webio::run_tests_in_browser! {}
+
+use std::time::Duration;
+use webio::time::{animation_frame, animation_frames, Instant};
+
+#[webio::test]
+async fn single_animation_frame() {
+    let then = Instant::now();
+    let timestamp = animation_frame().await;
+    assert!(timestamp.elapsed() < Duration::from_millis(500));
+    assert!(then.elapsed() < Duration::from_millis(500));
+}
+
+#[webio::test]
+async fn animation_frames_delta() {
+    let frames = animation_frames();
+    let first = frames.tick().await;
+    assert_eq!(first.delta(), Duration::ZERO);
+    let second = frames.tick().await;
+    assert!(second.timestamp() > first.timestamp());
+    assert_eq!(
+        second.delta(),
+        second.timestamp().duration_since(first.timestamp())
+    );
+}
+
+#[webio::test]
+async fn dropped_animation_frame_is_cancelled() {
+    drop(animation_frame());
+    animation_frame().await;
+}
@@ -128,3 +128,81 @@ async fn async_multi_with_ret() {
     assert_eq!(listener.listen_next().await.unwrap(), 2);
     assert_eq!(listener.listen_next().await.unwrap(), 1);
 }
+
+fn sync_burst_register(
+    policy: callback::BufferPolicy,
+) -> callback::multi::Listener<u32> {
+    let register = callback::multi::SyncRegister::new(|mut callback| {
+        callback(1);
+        callback(2);
+        callback(3);
+    })
+    .buffered(policy);
+    register.listen(|data| data)
+}
+
+#[webio::test]
+async fn sync_multi_buffer_coalesce_latest() {
+    let listener = sync_burst_register(callback::BufferPolicy::CoalesceLatest);
+    assert_eq!(listener.listen_next().await.unwrap(), 3);
+    assert!(listener.listen_next().await.is_err());
+    assert_eq!(listener.dropped(), 2);
+}
+
+#[webio::test]
+async fn sync_multi_buffer_unbounded() {
+    let listener = sync_burst_register(callback::BufferPolicy::Unbounded);
+    assert_eq!(listener.listen_next().await.unwrap(), 1);
+    assert_eq!(listener.listen_next().await.unwrap(), 2);
+    assert_eq!(listener.listen_next().await.unwrap(), 3);
+    assert!(listener.listen_next().await.is_err());
+    assert_eq!(listener.dropped(), 0);
+}
+
+#[webio::test]
+async fn sync_multi_buffer_drop_oldest() {
+    let listener = sync_burst_register(callback::BufferPolicy::DropOldest(2));
+    assert_eq!(listener.listen_next().await.unwrap(), 2);
+    assert_eq!(listener.listen_next().await.unwrap(), 3);
+    assert!(listener.listen_next().await.is_err());
+    assert_eq!(listener.dropped(), 1);
+}
+
+#[webio::test]
+async fn sync_multi_buffer_drop_newest() {
+    let listener = sync_burst_register(callback::BufferPolicy::DropNewest(2));
+    assert_eq!(listener.listen_next().await.unwrap(), 1);
+    assert_eq!(listener.listen_next().await.unwrap(), 2);
+    assert!(listener.listen_next().await.is_err());
+    assert_eq!(listener.dropped(), 1);
+}
+
+#[webio::test]
+async fn sync_multi_buffer_policy_change_shrinks() {
+    let listener = sync_burst_register(callback::BufferPolicy::Unbounded);
+    listener.set_buffer_policy(callback::BufferPolicy::DropOldest(1));
+    assert_eq!(listener.listen_next().await.unwrap(), 3);
+    assert!(listener.listen_next().await.is_err());
+    assert_eq!(listener.dropped(), 2);
+}
+
+#[webio::test]
+#[should_panic]
+async fn sync_multi_buffer_zero_capacity_panics() {
+    sync_burst_register(callback::BufferPolicy::DropOldest(0));
+}
+
+// Recovering from the panic requires unwinding, which WASM does not support.
+#[cfg(panic = "unwind")]
+#[webio::test]
+async fn sync_multi_buffer_rejected_policy_keeps_buffer() {
+    let listener = sync_burst_register(callback::BufferPolicy::Unbounded);
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        listener.set_buffer_policy(callback::BufferPolicy::DropNewest(0));
+    }));
+    assert!(result.is_err());
+    assert_eq!(listener.listen_next().await.unwrap(), 1);
+    assert_eq!(listener.listen_next().await.unwrap(), 2);
+    assert_eq!(listener.listen_next().await.unwrap(), 3);
+    assert_eq!(listener.dropped(), 0);
+}
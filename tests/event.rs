@@ -1,6 +1,14 @@
+#![cfg(target_arch = "wasm32")]
+
 webio::run_tests_in_browser! {}
 
-use webio::{event::EventType, EventType};
+use std::time::Duration;
+use webio::{
+    callback::BufferPolicy,
+    event::EventType,
+    time::{with_timeout, Instant},
+    EventType,
+};
 
 macro_rules! make_event {
     (
@@ -258,3 +266,57 @@ async fn custom_click() {
         .unwrap();
     listener.listen_next().await.unwrap();
 }
+
+fn click_three_times(element: &TempElement) {
+    for _ in 0 .. 3 {
+        element
+            .js_object
+            .dispatch_event(&web_sys::MouseEvent::new("click").unwrap())
+            .unwrap();
+    }
+}
+
+#[webio::test]
+async fn debounced_click() {
+    let element = TempElement::create("button");
+    let listener = webio::event::Click
+        .add_listener(&element.js_object)
+        .buffered(BufferPolicy::Unbounded)
+        .debounce(Duration::from_millis(20));
+    click_three_times(&element);
+    listener.listen_next().await.unwrap();
+    let next = with_timeout(Duration::from_millis(50), listener.listen_next());
+    assert!(next.await.is_err());
+}
+
+#[webio::test]
+async fn throttled_click() {
+    let element = TempElement::create("button");
+    let listener = webio::event::Click
+        .add_listener(&element.js_object)
+        .buffered(BufferPolicy::Unbounded)
+        .throttle(Duration::from_millis(20));
+    click_three_times(&element);
+    listener.listen_next().await.unwrap();
+    let next = with_timeout(Duration::from_millis(50), listener.listen_next());
+    assert!(next.await.is_err());
+    click_three_times(&element);
+    listener.listen_next().await.unwrap();
+}
+
+#[webio::test]
+async fn instant_from_event() {
+    let element = TempElement::create("button");
+    let listener = webio::event::Click.add_listener(&element.js_object);
+    let before = Instant::now();
+    element
+        .js_object
+        .dispatch_event(&web_sys::MouseEvent::new("click").unwrap())
+        .unwrap();
+    let event = listener.listen_next().await.unwrap();
+    let clicked = Instant::from_event(&event);
+    // Timestamps of events might be coarsened by the browser.
+    assert!(clicked + Duration::from_millis(1) >= before);
+    let elapsed = Instant::now().saturating_duration_since(clicked);
+    assert!(elapsed < Duration::from_secs(1));
+}
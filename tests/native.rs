@@ -0,0 +1,99 @@
+#![cfg(not(target_arch = "wasm32"))]
+
+use std::{cell::RefCell, rc::Rc, time::Duration};
+use webio::{
+    native::{self, Clock, Runtime},
+    task,
+    time::{interval, timeout, Instant},
+};
+
+#[test]
+fn virtual_clock_jumps_to_timers() {
+    let started = std::time::Instant::now();
+    let elapsed = Runtime::new().clock(Clock::Virtual).block_on(async {
+        let then = Instant::now();
+        timeout(Duration::from_secs(60)).await;
+        then.elapsed()
+    });
+    assert_eq!(elapsed, Duration::from_secs(60));
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn virtual_clock_interval_ticks() {
+    Runtime::new().clock(Clock::Virtual).block_on(async {
+        let then = Instant::now();
+        let handle = interval(Duration::from_millis(250));
+        for i in 1 .. 4 {
+            handle.tick().await;
+            assert_eq!(then.elapsed(), Duration::from_millis(250) * i);
+        }
+    });
+}
+
+#[test]
+fn timers_fire_in_deadline_order() {
+    let order = Rc::new(RefCell::new(Vec::new()));
+    Runtime::new().clock(Clock::Virtual).block_on({
+        let order = order.clone();
+        async move {
+            let spawn_timeout = |millis: u64| {
+                let order = order.clone();
+                task::spawn(async move {
+                    timeout(Duration::from_millis(millis)).await;
+                    order.borrow_mut().push(millis);
+                })
+            };
+            let handles =
+                [spawn_timeout(30), spawn_timeout(10), spawn_timeout(20)];
+            for handle in handles {
+                handle.await.unwrap();
+            }
+        }
+    });
+    assert_eq!(*order.borrow(), [10, 20, 30]);
+}
+
+#[test]
+fn real_clock_sleeps() {
+    let elapsed = native::block_on(async {
+        let then = Instant::now();
+        timeout(Duration::from_millis(20)).await;
+        then.elapsed()
+    });
+    assert!(elapsed >= Duration::from_millis(20));
+}
+
+#[test]
+fn real_clock_continues_from_virtual_clock() {
+    let then = Runtime::new().clock(Clock::Virtual).block_on(async {
+        let then = Instant::now();
+        timeout(Duration::from_secs(3600)).await;
+        then
+    });
+    let elapsed = native::block_on(async {
+        timeout(Duration::from_millis(10)).await;
+        then.elapsed()
+    });
+    assert!(elapsed >= Duration::from_secs(3600));
+    assert!(elapsed < Duration::from_secs(3605));
+}
+
+#[test]
+fn tasks_survive_between_block_on_calls() {
+    let handle = task::spawn(async {
+        task::yield_now().await;
+        task::yield_now().await;
+        5
+    });
+    native::block_on(task::yield_now());
+    assert_eq!(native::block_on(handle).unwrap(), 5);
+}
+
+#[test]
+#[should_panic]
+fn nested_block_on_panics() {
+    native::block_on(async {
+        native::block_on(async {});
+    });
+}
@@ -1,7 +1,24 @@
-use std::rc::Rc;
+use futures::{stream::StreamExt, FutureExt};
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
 
 use webio::{
-    sync::{Mutex, RwLock},
+    sync::{
+        channel::{broadcast, mpsc, oneshot, watch},
+        AcquireError,
+        MappedMutexGuard,
+        Mutex,
+        MutexGuard,
+        Notify,
+        ReadGuard,
+        RwLock,
+        Semaphore,
+        TryAcquireError,
+        UpgradeableReadGuard,
+        WriteGuard,
+    },
     task,
 };
 
@@ -286,3 +303,420 @@ async fn rwlock_fairness() {
 
     webio::try_join!(task0, task1, task2, task3, task4).unwrap();
 }
+
+#[webio::test]
+async fn semaphore_limits_concurrency() {
+    let semaphore = Rc::new(Semaphore::new(2));
+    let running = Rc::new(Cell::new(0));
+    let mut handles = Vec::new();
+    for _ in 0 .. 5 {
+        let semaphore = semaphore.clone();
+        let running = running.clone();
+        handles.push(task::spawn(async move {
+            let _permit = semaphore.acquire(1).await.unwrap();
+            running.set(running.get() + 1);
+            assert!(running.get() <= 2);
+            task::yield_now().await;
+            running.set(running.get() - 1);
+        }));
+    }
+    for handle in handles {
+        handle.await.unwrap();
+    }
+    assert_eq!(semaphore.available_permits(), 2);
+}
+
+#[webio::test]
+async fn semaphore_fairness() {
+    let semaphore = Rc::new(Semaphore::new(1));
+    let order = Rc::new(RefCell::new(Vec::new()));
+    let permit = semaphore.try_acquire(1).unwrap();
+    let task0 = task::spawn({
+        let semaphore = semaphore.clone();
+        let order = order.clone();
+        async move {
+            let permit = semaphore.acquire(2).await.unwrap();
+            assert_eq!(permit.permits(), 2);
+            order.borrow_mut().push(0);
+        }
+    });
+    let task1 = task::spawn({
+        let semaphore = semaphore.clone();
+        let order = order.clone();
+        async move {
+            let _permit = semaphore.acquire(1).await.unwrap();
+            order.borrow_mut().push(1);
+        }
+    });
+    task::yield_now().await;
+    assert_eq!(
+        semaphore.try_acquire(1).unwrap_err(),
+        TryAcquireError::NoPermits
+    );
+    semaphore.add_permits(1);
+    task::yield_now().await;
+    assert!(order.borrow().is_empty());
+    drop(permit);
+
+    webio::try_join!(task0, task1).unwrap();
+    assert_eq!(*order.borrow(), [0, 1]);
+    assert_eq!(semaphore.available_permits(), 2);
+}
+
+#[webio::test]
+async fn semaphore_close_and_forget() {
+    let semaphore = Rc::new(Semaphore::new(1));
+    let permit = semaphore.acquire(1).await.unwrap();
+    let waiting = task::spawn({
+        let semaphore = semaphore.clone();
+        async move { semaphore.acquire(1).await.map(|_| ()) }
+    });
+    task::yield_now().await;
+    semaphore.close();
+    assert!(semaphore.is_closed());
+    assert_eq!(waiting.await.unwrap(), Err(AcquireError));
+    assert_eq!(semaphore.try_acquire(1).unwrap_err(), TryAcquireError::Closed);
+
+    permit.forget();
+    assert_eq!(semaphore.available_permits(), 0);
+}
+
+#[webio::test]
+async fn notify_one_in_order_and_permit() {
+    let notify = Rc::new(Notify::new());
+    let order = Rc::new(RefCell::new(Vec::new()));
+    let mut handles = Vec::new();
+    for i in 0 .. 3 {
+        let notify = notify.clone();
+        let order = order.clone();
+        handles.push(task::spawn(async move {
+            notify.notified().await;
+            order.borrow_mut().push(i);
+        }));
+    }
+    task::yield_now().await;
+    for _ in 0 .. 3 {
+        notify.notify_one();
+        task::yield_now().await;
+    }
+    for handle in handles {
+        handle.await.unwrap();
+    }
+    assert_eq!(*order.borrow(), [0, 1, 2]);
+
+    notify.notify_one();
+    notify.notify_one();
+    notify.notified().await;
+    let woken = Rc::new(Cell::new(false));
+    let waiting = task::spawn({
+        let notify = notify.clone();
+        let woken = woken.clone();
+        async move {
+            notify.notified().await;
+            woken.set(true);
+        }
+    });
+    task::yield_now().await;
+    assert!(!woken.get());
+    notify.notify_one();
+    waiting.await.unwrap();
+    assert!(woken.get());
+}
+
+#[webio::test]
+async fn notify_waiters_wakes_all_without_permit() {
+    let notify = Rc::new(Notify::new());
+    let woken = Rc::new(Cell::new(0));
+    let mut handles = Vec::new();
+    for _ in 0 .. 3 {
+        let notify = notify.clone();
+        let woken = woken.clone();
+        handles.push(task::spawn(async move {
+            notify.notified().await;
+            woken.set(woken.get() + 1);
+        }));
+    }
+    task::yield_now().await;
+    let created = notify.notified();
+    notify.notify_waiters();
+    created.await;
+    for handle in handles {
+        handle.await.unwrap();
+    }
+    assert_eq!(woken.get(), 3);
+
+    notify.notify_waiters();
+    let late = task::spawn({
+        let notify = notify.clone();
+        let woken = woken.clone();
+        async move {
+            notify.notified().await;
+            woken.set(woken.get() + 1);
+        }
+    });
+    task::yield_now().await;
+    assert_eq!(woken.get(), 3);
+    late.abort();
+}
+
+#[webio::test]
+async fn notify_stale_waiter_keeps_new_waiter() {
+    let notify = Notify::new();
+    let mut stale = notify.notified();
+    assert!((&mut stale).now_or_never().is_none());
+    notify.notify_waiters();
+    let mut fresh = notify.notified();
+    assert!((&mut fresh).now_or_never().is_none());
+    assert!((&mut stale).now_or_never().is_some());
+    notify.notify_one();
+    assert!((&mut fresh).now_or_never().is_some());
+}
+
+#[webio::test]
+async fn oneshot_send_and_close() {
+    let (sender, receiver) = oneshot::channel();
+    let handle = task::spawn(receiver);
+    task::yield_now().await;
+    assert!(!sender.is_closed());
+    sender.send(5).unwrap();
+    assert_eq!(handle.await.unwrap(), Ok(5));
+
+    let (sender, receiver) = oneshot::channel::<u8>();
+    drop(sender);
+    assert_eq!(receiver.await, Err(oneshot::RecvError));
+
+    let (sender, mut receiver) = oneshot::channel();
+    assert_eq!(receiver.try_recv(), Err(oneshot::TryRecvError::Empty));
+    let handle = task::spawn(async move {
+        sender.closed().await;
+        sender.send(3)
+    });
+    task::yield_now().await;
+    receiver.close();
+    assert_eq!(handle.await.unwrap(), Err(3));
+}
+
+#[webio::test]
+async fn mpsc_bounded_fairness() {
+    let (sender, mut receiver) = mpsc::channel(1);
+    sender.send(0).await.unwrap();
+    let mut handles = Vec::new();
+    for i in 1 .. 4 {
+        let sender = sender.clone();
+        handles.push(task::spawn(async move { sender.send(i).await }));
+    }
+    task::yield_now().await;
+    assert!(matches!(sender.try_send(9), Err(mpsc::TrySendError::Full(9))));
+    drop(sender);
+
+    let mut received = Vec::new();
+    while let Some(i) = receiver.recv().await {
+        received.push(i);
+    }
+    assert_eq!(received, [0, 1, 2, 3]);
+    for handle in handles {
+        handle.await.unwrap().unwrap();
+    }
+}
+
+#[webio::test]
+async fn mpsc_unbounded_stream_and_close() {
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+    for i in 0 .. 3 {
+        sender.send(i).unwrap();
+    }
+    receiver.close();
+    assert!(sender.is_closed());
+    assert_eq!(sender.send(3).unwrap_err().0, 3);
+    assert_eq!(receiver.collect::<Vec<_>>().await, [0, 1, 2]);
+
+    let (sender, mut receiver) = mpsc::unbounded_channel::<u8>();
+    assert_eq!(receiver.try_recv(), Err(mpsc::TryRecvError::Empty));
+    drop(sender);
+    assert_eq!(receiver.try_recv(), Err(mpsc::TryRecvError::Disconnected));
+}
+
+#[webio::test]
+async fn broadcast_every_receiver_and_lag() {
+    let (sender, mut receiver0) = broadcast::channel(2);
+    let mut receiver1 = sender.subscribe();
+    assert_eq!(sender.receiver_count(), 2);
+
+    let handle = task::spawn(async move {
+        let mut received = Vec::new();
+        while let Ok(i) = receiver1.recv().await {
+            received.push(i);
+        }
+        received
+    });
+    task::yield_now().await;
+    for i in 0 .. 4 {
+        assert_eq!(sender.send(i), Ok(2));
+        task::yield_now().await;
+    }
+
+    assert_eq!(receiver0.len(), 4);
+    assert_eq!(receiver0.recv().await, Err(broadcast::RecvError::Lagged(2)));
+    assert_eq!(receiver0.recv().await, Ok(2));
+    let mut late = receiver0.resubscribe();
+    drop(sender);
+    assert_eq!(receiver0.collect::<Vec<_>>().await, [Ok(3)]);
+    assert_eq!(late.try_recv(), Err(broadcast::TryRecvError::Closed));
+    assert_eq!(handle.await.unwrap(), [0, 1, 2, 3]);
+}
+
+#[webio::test]
+async fn watch_changes() {
+    let (sender, mut receiver0) = watch::channel(0);
+    let receiver1 = receiver0.clone();
+    assert_eq!(receiver0.has_changed(), Ok(false));
+
+    let handle =
+        task::spawn(async move { receiver1.collect::<Vec<_>>().await });
+    task::yield_now().await;
+    sender.send(1).unwrap();
+    sender.send_modify(|value| *value += 1);
+    task::yield_now().await;
+    sender.send_replace(3);
+    task::yield_now().await;
+
+    assert_eq!(receiver0.has_changed(), Ok(true));
+    receiver0.changed().await.unwrap();
+    assert_eq!(*receiver0.borrow_and_update(), 3);
+    drop(sender);
+    assert_eq!(receiver0.changed().await, Err(watch::RecvError));
+    assert_eq!(handle.await.unwrap(), [2, 3]);
+}
+
+#[webio::test]
+async fn mutex_owned_and_mapped_guards() {
+    let mutex = Rc::new(Mutex::new((0, String::new())));
+    let mut guard = mutex.clone().lock_owned().await;
+    assert!(mutex.clone().try_lock_owned().is_none());
+    assert!(format!("{:?}", mutex).contains("<locked>"));
+    let handle = task::spawn(async move {
+        task::yield_now().await;
+        guard.0 += 1;
+    });
+    let waiting = task::spawn({
+        let mutex = mutex.clone();
+        async move {
+            let guard = mutex.lock().await;
+            let mut name = MutexGuard::map(guard, |data| &mut data.1);
+            name.push_str("web");
+            let mut name = MappedMutexGuard::map(name, String::as_mut_str);
+            name.make_ascii_uppercase();
+        }
+    });
+    webio::try_join!(handle, waiting).unwrap();
+    assert_eq!(*mutex.lock().await, (1, String::from("WEB")));
+}
+
+#[webio::test]
+async fn rwlock_owned_and_mapped_guards() {
+    let rwlock = Rc::new(RwLock::new((0, 0)));
+    let read0 = rwlock.clone().read_owned().await;
+    let read1 = rwlock.clone().try_read_owned().unwrap();
+    assert!(rwlock.clone().try_write_owned().is_none());
+    let writer = task::spawn({
+        let rwlock = rwlock.clone();
+        async move {
+            let mut guard = rwlock.write_owned().await;
+            guard.0 = 1;
+        }
+    });
+    task::yield_now().await;
+    assert_eq!(*read0, (0, 0));
+    drop(read0);
+    assert_eq!(*read1, (0, 0));
+    drop(read1);
+    writer.await.unwrap();
+
+    let guard = WriteGuard::map(rwlock.write().await, |data| &mut data.1);
+    assert!(rwlock.try_read().is_none());
+    drop(guard);
+    {
+        let mut second =
+            WriteGuard::map(rwlock.write().await, |data| &mut data.1);
+        *second = 2;
+    }
+    let first = ReadGuard::map(rwlock.read().await, |data| &data.0);
+    let second = ReadGuard::map(rwlock.try_read().unwrap(), |data| &data.1);
+    assert_eq!((*first, *second), (1, 2));
+}
+
+#[webio::test]
+async fn rwlock_downgrade() {
+    let rwlock = Rc::new(RwLock::new(0));
+    let mut guard = rwlock.write().await;
+    let reader = task::spawn({
+        let rwlock = rwlock.clone();
+        async move { *rwlock.read().await }
+    });
+    let writer = task::spawn({
+        let rwlock = rwlock.clone();
+        async move {
+            let mut guard = rwlock.write().await;
+            *guard += 1;
+        }
+    });
+    let late_reader = task::spawn({
+        let rwlock = rwlock.clone();
+        async move { *rwlock.read().await }
+    });
+    task::yield_now().await;
+    *guard = 1;
+    let guard = guard.downgrade();
+    assert_eq!(*guard, 1);
+    assert_eq!(reader.await.unwrap(), 1);
+    assert!(rwlock.try_read().is_none());
+    drop(guard);
+    writer.await.unwrap();
+    assert_eq!(late_reader.await.unwrap(), 2);
+}
+
+#[webio::test]
+async fn rwlock_upgradeable_read() {
+    let rwlock = Rc::new(RwLock::new(0));
+    let reader = rwlock.read().await;
+    let guard = rwlock.upgradeable_read().await;
+    assert!(rwlock.try_upgradeable_read().is_none());
+    assert!(rwlock.try_write().is_none());
+    assert_eq!((*reader, *guard), (0, 0));
+
+    let guard = UpgradeableReadGuard::try_upgrade(guard).unwrap_err();
+    assert!(UpgradeableReadGuard::upgrade(guard).now_or_never().is_none());
+
+    let upgrader = task::spawn({
+        let rwlock = rwlock.clone();
+        async move {
+            let guard = rwlock.try_upgradeable_read().unwrap();
+            let mut guard = UpgradeableReadGuard::upgrade(guard).await;
+            *guard = 1;
+        }
+    });
+    task::yield_now().await;
+    assert!(rwlock.try_read().is_none());
+    let late_reader = task::spawn({
+        let rwlock = rwlock.clone();
+        async move { *rwlock.read().await }
+    });
+    task::yield_now().await;
+    drop(reader);
+    upgrader.await.unwrap();
+    assert_eq!(late_reader.await.unwrap(), 1);
+
+    let guard = rwlock.upgradeable_read().await;
+    let guard = UpgradeableReadGuard::downgrade(guard);
+    let other = rwlock.try_upgradeable_read().unwrap();
+    let mut guard = match UpgradeableReadGuard::try_upgrade(other) {
+        Ok(_) => panic!("upgraded while read-locked"),
+        Err(other) => {
+            drop(guard);
+            UpgradeableReadGuard::try_upgrade(other).unwrap()
+        },
+    };
+    *guard = 2;
+    drop(guard);
+    assert_eq!(*rwlock.read().await, 2);
+}
@@ -1,5 +1,15 @@
+use std::{
+    cell::{Cell, RefCell},
+    future,
+    rc::Rc,
+    time::Duration,
+};
 use wasm_bindgen_test::wasm_bindgen_test;
-use webio::{join, task};
+use webio::{
+    join,
+    task,
+    time::{timeout, Instant},
+};
 
 #[wasm_bindgen_test]
 fn triple_spawn_join_with_detach() {
@@ -26,6 +36,392 @@ async fn triple_spawn_join_with_test_macro() {
     assert_eq!((first.unwrap(), second.unwrap(), third.unwrap()), (3, 5, 7));
 }
 
+#[cfg(target_arch = "wasm32")]
 async fn _assert_test_macro() {
     let (): () = triple_spawn_join_with_test_macro().await;
 }
+
+struct DropFlag(Rc<Cell<bool>>);
+
+impl Drop for DropFlag {
+    fn drop(&mut self) {
+        self.0.set(true);
+    }
+}
+
+#[webio::test]
+async fn abort_pending_task() {
+    let dropped = Rc::new(Cell::new(false));
+    let handle = task::spawn({
+        let flag = DropFlag(dropped.clone());
+        async move {
+            let _flag = flag;
+            future::pending::<()>().await;
+        }
+    });
+    task::yield_now().await;
+    assert!(!dropped.get());
+    handle.abort();
+    let error = handle.await.unwrap_err();
+    assert!(error.is_cancelled());
+    assert!(dropped.get());
+}
+
+#[webio::test]
+async fn abort_through_abort_handle() {
+    let handle = task::spawn(future::pending::<u32>());
+    let abort_handle = handle.abort_handle();
+    abort_handle.clone().abort();
+    assert!(abort_handle.is_aborted());
+    assert!(handle.await.unwrap_err().is_cancelled());
+}
+
+#[webio::test]
+async fn abort_completed_task() {
+    let handle = task::spawn(async { 3 });
+    task::yield_now().await;
+    handle.abort();
+    assert_eq!(handle.await.unwrap(), 3);
+}
+
+#[webio::test]
+async fn join_set_joins_all() {
+    let mut set = task::JoinSet::new();
+    for i in 0 .. 5u32 {
+        set.spawn(async move {
+            task::yield_now().await;
+            i * 2
+        });
+    }
+    assert_eq!(set.len(), 5);
+
+    let mut outputs = Vec::new();
+    while let Some(result) = set.join_next().await {
+        outputs.push(result.unwrap());
+    }
+    outputs.sort();
+    assert_eq!(outputs, [0, 2, 4, 6, 8]);
+    assert!(set.is_empty());
+}
+
+#[webio::test]
+async fn join_set_abort_single() {
+    let mut set = task::JoinSet::new();
+    let abort_handle = set.spawn(future::pending::<u32>());
+    set.spawn(async { 7 });
+    abort_handle.abort();
+
+    let mut cancelled = 0;
+    let mut outputs = Vec::new();
+    while let Some(result) = set.join_next().await {
+        match result {
+            Ok(output) => outputs.push(output),
+            Err(error) => {
+                assert!(error.is_cancelled());
+                cancelled += 1;
+            },
+        }
+    }
+    assert_eq!(outputs, [7]);
+    assert_eq!(cancelled, 1);
+}
+
+#[webio::test]
+async fn join_set_aborts_on_drop() {
+    let dropped = Rc::new(Cell::new(false));
+    let mut set = task::JoinSet::new();
+    set.spawn({
+        let flag = DropFlag(dropped.clone());
+        async move {
+            let _flag = flag;
+            future::pending::<()>().await;
+        }
+    });
+    task::yield_now().await;
+    drop(set);
+    task::yield_now().await;
+    assert!(dropped.get());
+}
+
+webio::task_local! {
+    static REQUEST_ID: u32;
+}
+
+#[webio::test]
+async fn task_local_isolated_between_tasks() {
+    let first = task::spawn(REQUEST_ID.scope(1, async {
+        task::yield_now().await;
+        let before = REQUEST_ID.get();
+        task::yield_now().await;
+        (before, REQUEST_ID.get())
+    }));
+    let second = task::spawn(REQUEST_ID.scope(2, async {
+        task::yield_now().await;
+        let before = REQUEST_ID.get();
+        task::yield_now().await;
+        (before, REQUEST_ID.get())
+    }));
+    assert_eq!(first.await.unwrap(), (1, 1));
+    assert_eq!(second.await.unwrap(), (2, 2));
+    assert!(REQUEST_ID.try_with(|_| ()).is_err());
+}
+
+#[webio::test]
+async fn task_local_nested_scopes() {
+    REQUEST_ID
+        .scope(1, async {
+            REQUEST_ID.sync_scope(2, || assert_eq!(REQUEST_ID.get(), 2));
+            REQUEST_ID
+                .scope(3, async {
+                    task::yield_now().await;
+                    assert_eq!(REQUEST_ID.get(), 3);
+                })
+                .await;
+            assert_eq!(REQUEST_ID.get(), 1);
+        })
+        .await;
+}
+
+#[webio::test]
+async fn consume_budget_yields_to_event_loop() {
+    let previous_budget = task::budget();
+    task::set_budget(Duration::from_millis(5));
+    let timer_fired = Rc::new(Cell::new(false));
+    task::detach({
+        let timer_fired = timer_fired.clone();
+        async move {
+            timeout(Duration::ZERO).await;
+            timer_fired.set(true);
+        }
+    });
+
+    let then = Instant::now();
+    while then.elapsed() < Duration::from_millis(50) {
+        task::consume_budget().await;
+    }
+    assert!(timer_fired.get());
+    task::set_budget(previous_budget);
+}
+
+#[webio::test]
+async fn maybe_yield_only_when_budget_exhausted() {
+    let previous_budget = task::budget();
+    task::set_budget(Duration::from_secs(3600));
+    assert!(!task::maybe_yield().await);
+
+    task::set_budget(Duration::ZERO);
+    let timer_fired = Rc::new(Cell::new(false));
+    task::detach({
+        let timer_fired = timer_fired.clone();
+        async move {
+            timeout(Duration::ZERO).await;
+            timer_fired.set(true);
+        }
+    });
+    let then = Instant::now();
+    let mut yields = 0;
+    while !timer_fired.get() && then.elapsed() < Duration::from_secs(1) {
+        if task::maybe_yield().await {
+            yields += 1;
+        }
+    }
+    assert!(timer_fired.get());
+    assert!(yields > 0);
+    task::set_budget(previous_budget);
+}
+
+#[webio::test]
+async fn budget_is_tracked_per_task() {
+    let previous_budget = task::budget();
+    task::set_budget(Duration::from_millis(20));
+    let busy = task::spawn(async {
+        let then = Instant::now();
+        while then.elapsed() < Duration::from_millis(30) {}
+        let exhausted = task::maybe_yield().await;
+        (exhausted, task::maybe_yield().await)
+    });
+    let other = task::spawn(task::maybe_yield());
+    assert_eq!(busy.await.unwrap(), (true, false));
+    assert!(!other.await.unwrap());
+    task::set_budget(previous_budget);
+}
+
+fn chain_microtasks(remaining: u32, done: Rc<Cell<bool>>) {
+    task::detach(async move {
+        match remaining.checked_sub(1) {
+            Some(remaining) => chain_microtasks(remaining, done),
+            None => done.set(true),
+        }
+    });
+}
+
+#[webio::test]
+async fn yield_microtask_resumes_before_macrotasks() {
+    let timer_fired = Rc::new(Cell::new(false));
+    task::detach({
+        let timer_fired = timer_fired.clone();
+        async move {
+            timeout(Duration::ZERO).await;
+            timer_fired.set(true);
+        }
+    });
+    task::yield_with(task::YieldStrategy::Microtask).await;
+    assert!(!timer_fired.get());
+}
+
+#[webio::test]
+async fn yield_macrotask_drains_microtasks() {
+    let done = Rc::new(Cell::new(false));
+    chain_microtasks(100, done.clone());
+    task::yield_with(task::YieldStrategy::Macrotask).await;
+    assert!(done.get());
+}
+
+#[webio::test]
+async fn yield_scheduler_drains_microtasks() {
+    let done = Rc::new(Cell::new(false));
+    chain_microtasks(100, done.clone());
+    task::yield_with(task::YieldStrategy::Scheduler).await;
+    assert!(done.get());
+}
+
+#[webio::test]
+async fn spawn_idle_exposes_deadline() {
+    assert!(task::idle_deadline().is_none());
+    let handle = task::spawn_idle(async {
+        let first = task::idle_deadline().is_some();
+        task::yield_now().await;
+        let second = task::idle_deadline().is_some();
+        (first, second)
+    });
+    assert_eq!(handle.await.unwrap(), (true, true));
+    assert!(task::idle_deadline().is_none());
+}
+
+#[webio::test]
+async fn builder_without_configuration() {
+    let handle = task::Builder::new().spawn(async { 4 });
+    assert_eq!(handle.await.unwrap(), 4);
+}
+
+#[webio::test]
+async fn builder_priority_order() {
+    let order = Rc::new(RefCell::new(Vec::new()));
+    let spawn_recording = |priority| {
+        let order = order.clone();
+        task::Builder::new().priority(priority).spawn(async move {
+            order.borrow_mut().push(priority);
+        })
+    };
+    let background = spawn_recording(task::Priority::Background);
+    let user_visible = spawn_recording(task::Priority::UserVisible);
+    let user_blocking = spawn_recording(task::Priority::UserBlocking);
+    webio::try_join!(background, user_visible, user_blocking).unwrap();
+    assert_eq!(
+        *order.borrow(),
+        [
+            task::Priority::UserBlocking,
+            task::Priority::UserVisible,
+            task::Priority::Background,
+        ]
+    );
+}
+
+#[webio::test]
+async fn builder_priority_task_resumes() {
+    let handle = task::Builder::new()
+        .priority(task::Priority::Background)
+        .spawn(async {
+            let mut sum = 0;
+            for i in 0 .. 5 {
+                task::yield_now().await;
+                sum += i;
+            }
+            sum
+        });
+    assert_eq!(handle.await.unwrap(), 10);
+}
+
+#[webio::test]
+async fn builder_priority_task_abort() {
+    let handle = task::Builder::new()
+        .priority(task::Priority::UserVisible)
+        .spawn(future::pending::<()>());
+    task::yield_with(task::YieldStrategy::Macrotask).await;
+    handle.abort();
+    assert!(handle.await.unwrap_err().is_cancelled());
+}
+
+#[cfg(debug_assertions)]
+#[webio::test]
+async fn named_task_is_listed_while_alive() {
+    let handle = task::Builder::new().name("listed").spawn(async {
+        task::yield_now().await;
+        3
+    });
+    let id = handle.id();
+    let info = task::tasks().into_iter().find(|info| info.id() == id).unwrap();
+    assert_eq!(info.name(), Some("listed"));
+    assert_eq!(info.state(), task::TaskState::Spawned);
+    assert_eq!(info.poll_count(), 0);
+    assert_eq!(info.location().file(), file!());
+    assert_eq!(handle.await.unwrap(), 3);
+    assert!(task::tasks().iter().all(|info| info.id() != id));
+}
+
+#[cfg(debug_assertions)]
+#[webio::test]
+async fn task_info_tracks_polls_and_abort() {
+    let handle = task::spawn(future::pending::<()>());
+    task::yield_now().await;
+    let find = |id| task::tasks().into_iter().find(|info| info.id() == id);
+    let info = find(handle.id()).unwrap();
+    assert_eq!(info.name(), None);
+    assert_eq!(info.state(), task::TaskState::Waiting);
+    assert_eq!(info.poll_count(), 1);
+    handle.abort();
+    assert_eq!(find(handle.id()).unwrap().state(), task::TaskState::Aborting);
+    let id = handle.id();
+    assert!(handle.await.unwrap_err().is_cancelled());
+    assert!(find(id).is_none());
+}
+
+#[cfg(debug_assertions)]
+#[webio::test]
+async fn yield_now_spawns_no_task() {
+    let yielding = task::spawn(task::yield_now());
+    // Runs while the first task is in the middle of yielding.
+    let tasks = task::spawn(async { task::tasks() }).await.unwrap();
+    assert!(tasks.iter().any(|info| info.id() == yielding.id()));
+    assert!(tasks.iter().all(|info| info.location().file() == file!()));
+    yielding.await.unwrap();
+}
+
+#[cfg(panic = "unwind")]
+#[webio::test]
+async fn catch_panics_without_panic() {
+    let handle = task::Builder::new().catch_panics(true).spawn(async {
+        task::yield_now().await;
+        5
+    });
+    assert_eq!(handle.await.unwrap(), 5);
+}
+
+#[cfg(panic = "unwind")]
+#[webio::test]
+async fn catch_panics_isolates_task() {
+    let failing = task::Builder::new().catch_panics(true).spawn(async {
+        task::yield_now().await;
+        panic!("task failed on purpose");
+    });
+    let healthy = task::spawn(async {
+        task::yield_now().await;
+        task::yield_now().await;
+        7
+    });
+    let error = failing.await.unwrap_err();
+    assert!(error.is_panic());
+    assert!(!error.is_cancelled());
+    assert_eq!(error.panic_message(), Some("task failed on purpose"));
+    assert_eq!(healthy.await.unwrap(), 7);
+}
@@ -1,5 +1,32 @@
-use std::time::Duration;
-use webio::time::{interval, timeout, Instant};
+use futures::stream::{self, Stream, StreamExt};
+use std::{
+    cell::{Cell, RefCell},
+    future::{self, Future},
+    pin::Pin,
+    rc::Rc,
+    time::Duration,
+};
+use webio::{
+    callback::BufferPolicy,
+    task,
+    time::{
+        idle,
+        idle_with_timeout,
+        interval,
+        mock,
+        perf,
+        sleep_until,
+        timeout,
+        with_timeout,
+        Elapsed,
+        Instant,
+        MissedTickBehavior,
+        SystemTime,
+        TimeStreamExt,
+        TimeoutExt,
+        UNIX_EPOCH,
+    },
+};
 
 #[webio::test]
 async fn timeout_and_instant() {
@@ -33,6 +60,95 @@ async fn interval_and_instant() {
     assert!(passed < time * 3 + Duration::from_millis(50));
 }
 
+#[webio::test]
+async fn mock_timeout_and_instant() {
+    mock::pause();
+    let then = Instant::now();
+    let time = Duration::from_millis(100);
+    let handle = task::spawn(timeout(time));
+    mock::advance(time).await;
+    handle.await.unwrap();
+    assert_eq!(then.elapsed(), time);
+    mock::resume();
+}
+
+#[webio::test]
+async fn mock_interval_and_instant() {
+    mock::pause();
+    let time = Duration::from_millis(100);
+    let handle = interval(time);
+    let then = Instant::now();
+
+    for ticks in 1 .. 4 {
+        mock::advance(time).await;
+        let scheduled = handle.tick().await;
+        assert_eq!(scheduled.duration_since(then), time * ticks);
+        assert_eq!(then.elapsed(), time * ticks);
+    }
+    mock::resume();
+}
+
+#[webio::test]
+async fn mock_advance_stops_before_later_timers() {
+    mock::pause();
+    let done = Rc::new(Cell::new(false));
+    let handle = task::spawn({
+        let done = done.clone();
+        async move {
+            timeout(Duration::from_secs(10)).await;
+            done.set(true);
+        }
+    });
+    mock::advance(Duration::from_secs(9)).await;
+    assert!(!done.get());
+    mock::advance(Duration::from_secs(1)).await;
+    assert!(done.get());
+    handle.await.unwrap();
+    mock::resume();
+}
+
+#[webio::test]
+async fn mock_advance_fires_chained_timers() {
+    mock::pause();
+    let then = Instant::now();
+    let handle = task::spawn(async move {
+        timeout(Duration::from_millis(30)).await;
+        let first = then.elapsed();
+        timeout(Duration::from_millis(30)).await;
+        (first, then.elapsed())
+    });
+    mock::advance(Duration::from_millis(100)).await;
+    assert_eq!(
+        handle.await.unwrap(),
+        (Duration::from_millis(30), Duration::from_millis(60))
+    );
+    assert_eq!(then.elapsed(), Duration::from_millis(100));
+    mock::resume();
+}
+
+#[webio::test]
+async fn mock_resume_continues_from_mock_clock() {
+    let real_wait = timeout(Duration::from_millis(60));
+    mock::pause();
+    let paused_at = Instant::now();
+    real_wait.await;
+    mock::advance(Duration::from_millis(10)).await;
+    mock::resume();
+    let elapsed = paused_at.elapsed();
+    assert!(elapsed >= Duration::from_millis(10));
+    assert!(elapsed < Duration::from_millis(50));
+}
+
+#[webio::test]
+async fn mock_resume_keeps_instant_monotonic() {
+    mock::pause();
+    let then = Instant::now();
+    mock::advance(Duration::from_secs(3600)).await;
+    mock::resume();
+    assert!(!mock::is_paused());
+    assert!(then.elapsed() >= Duration::from_secs(3600));
+}
+
 /*
  * TODO
 #[webio::test]
@@ -43,3 +159,393 @@ async fn panic_even_after_timeout() {
     panic!("This test should panic");
 }
 */
+
+#[webio::test]
+async fn idle_deadline_in_the_future() {
+    let deadline = idle().await;
+    assert!(!deadline.did_timeout());
+    let remaining = deadline.time_remaining();
+    assert!(remaining > Duration::ZERO);
+    assert!(remaining <= Duration::from_millis(50));
+}
+
+#[webio::test]
+async fn idle_with_timeout_reports_expired_timeout() {
+    let request = idle_with_timeout(Duration::from_millis(1));
+    let then = Instant::now();
+    while then.elapsed() < Duration::from_millis(5) {}
+    let deadline = request.await;
+    assert!(deadline.did_timeout());
+
+    let deadline = idle_with_timeout(Duration::from_secs(3600)).await;
+    assert!(!deadline.did_timeout());
+}
+
+#[webio::test]
+async fn idle_cancelled_on_drop() {
+    drop(idle());
+    idle_with_timeout(Duration::from_millis(100)).await;
+}
+
+#[webio::test]
+async fn with_timeout_completes_before_deadline() {
+    mock::pause();
+    let handle = task::spawn(with_timeout(Duration::from_secs(1), async {
+        timeout(Duration::from_millis(10)).await;
+        3
+    }));
+    mock::advance(Duration::from_millis(10)).await;
+    assert_eq!(handle.await.unwrap(), Ok(3));
+    mock::resume();
+}
+
+#[webio::test]
+async fn timeout_ext_elapses_and_drops_future() {
+    mock::pause();
+    let then = Instant::now();
+    let handle =
+        task::spawn(future::pending::<()>().timeout(Duration::from_secs(1)));
+    mock::advance(Duration::from_secs(1)).await;
+    assert_eq!(handle.await.unwrap(), Err(Elapsed));
+    assert_eq!(then.elapsed(), Duration::from_secs(1));
+    mock::resume();
+}
+
+#[webio::test]
+async fn sleep_until_and_reset() {
+    mock::pause();
+    let then = Instant::now();
+    let mut handle = sleep_until(then + Duration::from_secs(1));
+    assert_eq!(handle.deadline(), then + Duration::from_secs(1));
+    handle.reset(then + Duration::from_secs(3));
+    assert_eq!(handle.deadline(), then + Duration::from_secs(3));
+    let handle = task::spawn(async move {
+        (&mut handle).await;
+        let first = then.elapsed();
+        handle.reset(Instant::now() + Duration::from_secs(1));
+        handle.await;
+        (first, then.elapsed())
+    });
+    mock::advance(Duration::from_secs(4)).await;
+    assert_eq!(
+        handle.await.unwrap(),
+        (Duration::from_secs(3), Duration::from_secs(4))
+    );
+    mock::resume();
+}
+
+#[webio::test]
+async fn reset_wakes_waiting_task() {
+    mock::pause();
+    let then = Instant::now();
+    let shared = Rc::new(RefCell::new(timeout(Duration::from_secs(10))));
+    let handle = task::spawn({
+        let shared = shared.clone();
+        future::poll_fn(move |ctx| {
+            Pin::new(&mut *shared.borrow_mut()).poll(ctx)
+        })
+    });
+    task::yield_now().await;
+    shared.borrow_mut().reset(then + Duration::from_secs(1));
+    mock::advance(Duration::from_secs(1)).await;
+    handle.await.unwrap();
+    assert_eq!(then.elapsed(), Duration::from_secs(1));
+    mock::resume();
+}
+
+#[webio::test]
+async fn reset_same_handle_many_times() {
+    let then = Instant::now();
+    let mut handle = timeout(Duration::from_millis(10));
+    for i in 1 ..= 1000 {
+        handle
+            .reset(then + Duration::from_millis(20) + Duration::from_micros(i));
+    }
+    assert_eq!(handle.deadline(), then + Duration::from_millis(21));
+    handle.await;
+    assert!(then.elapsed() >= Duration::from_millis(21));
+}
+
+async fn tick_late(behavior: MissedTickBehavior) -> Vec<(u128, u128)> {
+    let (ticks, dropped) =
+        tick_late_buffered(behavior, BufferPolicy::Unbounded).await;
+    assert_eq!(dropped, 0);
+    ticks
+}
+
+async fn tick_late_buffered(
+    behavior: MissedTickBehavior,
+    policy: BufferPolicy,
+) -> (Vec<(u128, u128)>, u64) {
+    mock::pause();
+    let then = Instant::now();
+    let handle = interval(Duration::from_millis(100))
+        .with_missed_tick_behavior(behavior)
+        .buffered(policy);
+    mock::advance(Duration::from_millis(350)).await;
+    let mut ticks = Vec::new();
+    for _ in 0 .. 4 {
+        let now = Instant::now();
+        mock::advance(handle.next_tick().saturating_duration_since(now)).await;
+        let scheduled = handle.tick().await;
+        ticks.push((
+            scheduled.duration_since(then).as_millis(),
+            then.elapsed().as_millis(),
+        ));
+    }
+    mock::resume();
+    (ticks, handle.dropped())
+}
+
+#[webio::test]
+async fn interval_missed_ticks_burst() {
+    assert_eq!(
+        tick_late(MissedTickBehavior::Burst).await,
+        [(100, 350), (200, 350), (300, 350), (400, 400)]
+    );
+}
+
+#[webio::test]
+async fn interval_missed_ticks_delay() {
+    assert_eq!(
+        tick_late(MissedTickBehavior::Delay).await,
+        [(100, 350), (450, 450), (550, 550), (650, 650)]
+    );
+}
+
+#[webio::test]
+async fn interval_missed_ticks_skip() {
+    assert_eq!(
+        tick_late(MissedTickBehavior::Skip).await,
+        [(100, 350), (400, 400), (500, 500), (600, 600)]
+    );
+}
+
+#[webio::test]
+async fn interval_burst_drop_oldest() {
+    assert_eq!(
+        tick_late_buffered(
+            MissedTickBehavior::Burst,
+            BufferPolicy::DropOldest(2)
+        )
+        .await,
+        (vec![(200, 350), (300, 350), (400, 400), (500, 500)], 1)
+    );
+}
+
+#[webio::test]
+async fn interval_burst_drop_newest() {
+    assert_eq!(
+        tick_late_buffered(
+            MissedTickBehavior::Burst,
+            BufferPolicy::DropNewest(2)
+        )
+        .await,
+        (vec![(100, 350), (200, 350), (400, 400), (500, 500)], 1)
+    );
+}
+
+#[webio::test]
+async fn interval_burst_coalesce_latest() {
+    assert_eq!(
+        tick_late_buffered(
+            MissedTickBehavior::Burst,
+            BufferPolicy::CoalesceLatest
+        )
+        .await,
+        (vec![(300, 350), (400, 400), (500, 500), (600, 600)], 2)
+    );
+}
+
+#[webio::test]
+async fn interval_skip_ignores_buffer_policy() {
+    assert_eq!(
+        tick_late_buffered(
+            MissedTickBehavior::Skip,
+            BufferPolicy::CoalesceLatest
+        )
+        .await,
+        (vec![(100, 350), (400, 400), (500, 500), (600, 600)], 0)
+    );
+}
+
+#[webio::test]
+#[should_panic]
+async fn interval_buffer_zero_capacity_panics() {
+    let _ = interval(Duration::from_millis(100))
+        .buffered(BufferPolicy::DropNewest(0));
+}
+
+#[webio::test]
+async fn interval_stream_yields_unit() {
+    mock::pause();
+    let mut handle = interval(Duration::from_millis(100));
+    mock::advance(Duration::from_millis(100)).await;
+    assert_eq!(handle.next().await, Some(()));
+    mock::resume();
+}
+
+fn scheduled_stream(
+    schedule: Vec<(u64, &'static str)>,
+) -> impl Stream<Item = &'static str> {
+    stream::unfold(schedule.into_iter(), |mut schedule| async move {
+        let (delay, item) = schedule.next()?;
+        timeout(Duration::from_millis(delay)).await;
+        Some((item, schedule))
+    })
+}
+
+async fn collect_timed<S>(stream: S) -> Vec<(&'static str, u128)>
+where
+    S: Stream<Item = &'static str> + 'static,
+{
+    mock::pause();
+    let then = Instant::now();
+    let handle = task::spawn(
+        stream.map(move |item| (item, then.elapsed().as_millis())).collect(),
+    );
+    mock::advance(Duration::from_secs(1)).await;
+    let items = handle.await.unwrap();
+    mock::resume();
+    items
+}
+
+#[webio::test]
+async fn stream_debounce_yields_last_of_bursts() {
+    let source = scheduled_stream(vec![
+        (0, "a"),
+        (10, "b"),
+        (10, "c"),
+        (180, "d"),
+        (10, "e"),
+    ]);
+    assert_eq!(
+        collect_timed(source.debounce(Duration::from_millis(50))).await,
+        [("c", 70), ("e", 210)]
+    );
+}
+
+#[webio::test]
+async fn stream_throttle_yields_first_of_windows() {
+    let source = scheduled_stream(vec![
+        (0, "a"),
+        (10, "b"),
+        (10, "c"),
+        (40, "d"),
+        (10, "e"),
+        (130, "f"),
+    ]);
+    assert_eq!(
+        collect_timed(source.throttle(Duration::from_millis(50))).await,
+        [("a", 0), ("d", 60), ("f", 200)]
+    );
+}
+
+#[webio::test]
+async fn stream_throttle_keeps_item_after_window() {
+    mock::pause();
+    let mut throttled =
+        Box::pin(stream::iter(["a", "b"]).throttle(Duration::from_millis(50)));
+    assert_eq!(throttled.next().await, Some("a"));
+    mock::advance(Duration::from_millis(50)).await;
+    assert_eq!(throttled.next().await, Some("b"));
+    assert_eq!(throttled.next().await, None);
+    mock::resume();
+}
+
+#[webio::test]
+async fn system_time_since_epoch() {
+    let now = SystemTime::now();
+    let since_epoch = now.duration_since(UNIX_EPOCH).unwrap();
+    assert!(since_epoch > Duration::from_secs(1_600_000_000));
+    let later = now + Duration::from_millis(1500);
+    assert_eq!(later.duration_since(now), Ok(Duration::from_millis(1500)));
+    let error = now.duration_since(later).unwrap_err();
+    assert_eq!(error.duration(), Duration::from_millis(1500));
+    assert_eq!(later - Duration::from_millis(1500), now);
+    assert!(UNIX_EPOCH.checked_sub(Duration::from_secs(1)).is_some());
+}
+
+#[cfg(target_arch = "wasm32")]
+#[webio::test]
+async fn system_time_and_date() {
+    let date = js_sys::Date::new(&1_700_000_000_123.0.into());
+    let system_time = SystemTime::try_from(date.clone()).unwrap();
+    assert_eq!(
+        system_time.duration_since(UNIX_EPOCH),
+        Ok(Duration::from_millis(1_700_000_000_123))
+    );
+    assert_eq!(js_sys::Date::from(system_time).get_time(), date.get_time());
+    assert_eq!(
+        system_time.utc_offset_minutes(),
+        -(date.get_timezone_offset() as i32)
+    );
+}
+
+#[cfg(target_arch = "wasm32")]
+#[webio::test]
+async fn system_time_from_invalid_date() {
+    let date = js_sys::Date::new(&"invalid".into());
+    assert_eq!(SystemTime::from_date(&date), Err(webio::time::InvalidDate));
+    assert_eq!(SystemTime::try_from(date), Err(webio::time::InvalidDate));
+}
+
+#[webio::test]
+async fn instant_dom_high_res_timestamp() {
+    let instant = Instant::from_dom_high_res_timestamp(1500.25);
+    assert_eq!(instant.as_millis_f64(), 1500.25);
+    let later = instant + Duration::from_micros(250);
+    assert_eq!(later.as_millis_f64(), 1500.5);
+    assert_eq!(later.duration_since(instant), Duration::from_micros(250));
+}
+
+#[webio::test]
+#[should_panic]
+async fn instant_nan_timestamp_panics() {
+    let _ = Instant::from_dom_high_res_timestamp(f64::NAN);
+}
+
+#[webio::test]
+#[should_panic]
+async fn instant_infinite_timestamp_panics() {
+    let _ = Instant::from_dom_high_res_timestamp(f64::INFINITY);
+}
+
+#[webio::test]
+async fn perf_span_records_mark_and_measure() {
+    mock::pause();
+    let observer = perf::observe();
+    let then = Instant::now();
+    let span = perf::Span::new("span");
+    mock::advance(Duration::from_millis(30)).await;
+    drop(span);
+
+    let mark = observer.next_entry().await;
+    assert_eq!(mark.name(), "span");
+    assert_eq!(mark.kind(), perf::EntryKind::Mark);
+    assert_eq!(mark.start(), then);
+    assert_eq!(mark.duration(), Duration::ZERO);
+
+    let measure = observer.next_entry().await;
+    assert_eq!(measure.name(), "span");
+    assert_eq!(measure.kind(), perf::EntryKind::Measure);
+    assert_eq!(measure.start(), then);
+    assert_eq!(measure.duration(), Duration::from_millis(30));
+    mock::resume();
+}
+
+#[webio::test]
+async fn perf_measure_and_span_end() {
+    let observer = perf::observe();
+    let start = Instant::now();
+    let end = start + Duration::from_millis(5);
+    let measure = perf::measure("explicit", start, end);
+    assert_eq!(measure.duration(), Duration::from_millis(5));
+    assert_eq!(observer.next_entry().await, measure);
+
+    let span = perf::Span::new("ended");
+    assert_eq!(observer.next_entry().await.kind(), perf::EntryKind::Mark);
+    let measure = span.end();
+    assert_eq!(measure.name(), "ended");
+    assert_eq!(observer.next_entry().await, measure);
+}
@@ -17,9 +17,8 @@ impl Parse for Input {
         let mut arguments = Vec::new();
         if input.peek(token::Semi) {
             input.parse::<token::Semi>()?;
-            arguments.extend(
-                input.parse_terminated(Expr::parse, token::Comma)?.into_iter(),
-            );
+            arguments
+                .extend(input.parse_terminated(Expr::parse, token::Comma)?);
         }
         Ok(Self { method, arguments })
     }
@@ -1,3 +1,5 @@
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::quote;
 use syn::{
     parse::{Parse, ParseStream},
     token,
@@ -15,3 +17,67 @@ impl Parse for Input {
         Ok(Self { futures: futures.into_iter().collect() })
     }
 }
+
+impl Input {
+    /// Expansion of `join!` for native targets, where there are no promises:
+    /// the futures are polled in place by the current task, until all of them
+    /// complete.
+    pub fn native_expansion(&self) -> TokenStream {
+        let future_var_names = || {
+            (0 .. self.futures.len()).map(|i| {
+                Ident::new(&format!("future{}", i), Span::mixed_site())
+            })
+        };
+
+        let future_decls =
+            future_var_names().zip(&self.futures).map(|(ident, future)| {
+                quote! { let mut #ident = ::std::pin::pin!(#future); }
+            });
+
+        let output_var_names = || {
+            (0 .. self.futures.len()).map(|i| {
+                Ident::new(&format!("output{}", i), Span::mixed_site())
+            })
+        };
+
+        let output_decls =
+            output_var_names().map(|ident| quote! { let mut #ident = None; });
+
+        let polls = future_var_names().zip(output_var_names()).map(
+            |(future, output)| {
+                quote! {
+                    if #output.is_none() {
+                        match #future.as_mut().poll(ctx) {
+                            ::std::task::Poll::Ready(output_val) => {
+                                #output = Some(output_val);
+                            },
+                            ::std::task::Poll::Pending => all_ready = false,
+                        }
+                    }
+                }
+            },
+        );
+
+        let output_iter =
+            output_var_names().map(|ident| quote! { #ident.unwrap() });
+
+        quote! {
+            {
+                #(#future_decls)*
+                #(#output_decls)*
+                ::std::future::poll_fn(|ctx| {
+                    use ::std::future::Future;
+                    let mut all_ready = true;
+                    #(#polls)*
+                    if all_ready {
+                        ::std::task::Poll::Ready(())
+                    } else {
+                        ::std::task::Poll::Pending
+                    }
+                })
+                .await;
+                (#(#output_iter),*)
+            }
+        }
+    }
+}
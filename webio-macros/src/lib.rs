@@ -12,6 +12,10 @@ mod event_type;
 /// Joins a list of futures and returns their output into a tuple in the same
 /// order that the futures were given. Futures must be `'static`.
 ///
+/// In WASM, each future is converted into a promise, and the promises are
+/// joined through `Promise.all`. On native targets, which have no promises,
+/// the futures are polled in place by the current task instead.
+///
 /// Syntax:
 ///
 /// ```ignore
@@ -53,6 +57,7 @@ mod event_type;
 #[proc_macro]
 pub fn join(raw_input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(raw_input as join::Input);
+    let native_expanded = input.native_expansion();
     let futures = input.futures;
 
     let future_var_names = || {
@@ -116,7 +121,7 @@ pub fn join(raw_input: TokenStream) -> TokenStream {
     let output_iter =
         output_var_names().map(|ident| quote! { #ident.take().unwrap() });
 
-    let expanded = quote! {
+    let wasm_expanded = quote! {
         {
             #(#future_decls)*
             #(#output_decls)*
@@ -131,6 +136,16 @@ pub fn join(raw_input: TokenStream) -> TokenStream {
             (#(#output_iter),*)
         }
     };
+
+    let expanded = quote! {
+        {
+            #[cfg(target_arch = "wasm32")]
+            let joined = #wasm_expanded;
+            #[cfg(not(target_arch = "wasm32"))]
+            let joined = #native_expanded;
+            joined
+        }
+    };
     expanded.into()
 }
 
@@ -235,6 +250,11 @@ pub fn try_join(raw_input: TokenStream) -> TokenStream {
 /// right side of the "arm". Patterns must be irrefutable, typically just a
 /// variable name, or destructuring. Futures must be `'static`.
 ///
+/// In WASM, each future is converted into a promise, and the promises are
+/// raced through `Promise.any`. On native targets, which have no promises,
+/// the futures are polled in place by the current task instead, and those not
+/// selected are dropped.
+///
 /// Syntax:
 ///
 /// ```ignore
@@ -286,6 +306,7 @@ pub fn try_join(raw_input: TokenStream) -> TokenStream {
 #[proc_macro]
 pub fn select(raw_input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(raw_input as select::Input);
+    let native_expanded = input.native_expansion();
     let arms = input.arms;
 
     let future_var_names = || {
@@ -350,7 +371,7 @@ pub fn select(raw_input: TokenStream) -> TokenStream {
 
     let promise_var_names_iter = promise_var_names();
 
-    let expanded = quote! {
+    let wasm_expanded = quote! {
         {
             #(#future_decls)*
             #output_decl
@@ -366,6 +387,16 @@ pub fn select(raw_input: TokenStream) -> TokenStream {
         }
     };
 
+    let expanded = quote! {
+        {
+            #[cfg(target_arch = "wasm32")]
+            let selected = #wasm_expanded;
+            #[cfg(not(target_arch = "wasm32"))]
+            let selected = #native_expanded;
+            selected
+        }
+    };
+
     expanded.into_token_stream().into()
 }
 
@@ -467,7 +498,7 @@ pub fn main(raw_attribute: TokenStream, raw_input: TokenStream) -> TokenStream {
             "webio::main cannot be const",
         ));
     }
-    if input.sig.inputs.len() > 0 || input.sig.variadic.is_some() {
+    if !input.sig.inputs.is_empty() || input.sig.variadic.is_some() {
         error_dump.append(syn::Error::new(
             Span::call_site(),
             "webio::main function cannot receive parameters",
@@ -514,7 +545,9 @@ pub fn main(raw_attribute: TokenStream, raw_input: TokenStream) -> TokenStream {
 /// This macro converts an asynchronous test function into a synchronous one
 /// that can actually be tested by `wasm_bindgen_test`, and that invokes the
 /// asynchronous code. Under the hood, the asynchronous code is detached from
-/// the current call.
+/// the current call. On native targets, the test is a regular `#[test]`
+/// function instead, which blocks on the asynchronous code using
+/// `webio::native::block_on`.
 ///
 /// # Examples
 ///
@@ -571,7 +604,7 @@ pub fn test(raw_attribute: TokenStream, raw_input: TokenStream) -> TokenStream {
             "webio::test cannot be const",
         ));
     }
-    if input.sig.inputs.len() > 0 || input.sig.variadic.is_some() {
+    if !input.sig.inputs.is_empty() || input.sig.variadic.is_some() {
         error_dump.append(syn::Error::new(
             Span::call_site(),
             "webio::test function cannot receive parameters",
@@ -599,12 +632,22 @@ pub fn test(raw_attribute: TokenStream, raw_input: TokenStream) -> TokenStream {
             let body = input.block;
             let attrs = input.attrs;
             let expanded = quote! {
+                #[cfg(target_arch = "wasm32")]
                 #[::webio::wasm_bindgen_test::wasm_bindgen_test]
                 #(#attrs)*
                 #visibility async #fn_token #ident() {
                     webio::set_test_panic_hook();
                     let (): () = #body;
                 }
+
+                #[cfg(not(target_arch = "wasm32"))]
+                #[::core::prelude::v1::test]
+                #(#attrs)*
+                #visibility #fn_token #ident() {
+                    ::webio::native::block_on(async {
+                        let (): () = #body;
+                    })
+                }
             };
             expanded.into_token_stream().into()
         },
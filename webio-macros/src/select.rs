@@ -1,3 +1,5 @@
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::quote;
 use syn::{
     parse::{Parse, ParseStream},
     token,
@@ -34,3 +36,81 @@ impl Parse for Input {
         Ok(Self { arms: arms.into_iter().collect() })
     }
 }
+
+impl Input {
+    /// Expansion of `select!` for native targets, where there are no promises:
+    /// the futures are polled in place by the current task, until one of them
+    /// completes, and the others are dropped.
+    pub fn native_expansion(&self) -> TokenStream {
+        let future_var_names = || {
+            (0 .. self.arms.len()).map(|i| {
+                Ident::new(&format!("future{}", i), Span::mixed_site())
+            })
+        };
+
+        let variant_names = || {
+            (0 .. self.arms.len())
+                .map(|i| Ident::new(&format!("Arm{}", i), Span::mixed_site()))
+        };
+
+        let type_param_names = || {
+            (0 .. self.arms.len())
+                .map(|i| Ident::new(&format!("T{}", i), Span::mixed_site()))
+        };
+
+        let future_decls = future_var_names()
+            .zip(self.arms.iter().map(|arm| &arm.future))
+            .map(|(ident, future)| {
+                quote! { let mut #ident = ::std::pin::pin!(#future); }
+            });
+
+        let selected_enum_name = Ident::new("Selected", Span::mixed_site());
+        let variant_decls = variant_names()
+            .zip(type_param_names())
+            .map(|(variant, param)| quote! { #variant(#param) });
+        let type_params = type_param_names();
+        let selected_decl = quote! {
+            enum #selected_enum_name<#(#type_params),*> {
+                #(#variant_decls),*
+            }
+        };
+
+        let polls =
+            future_var_names().zip(variant_names()).map(|(future, variant)| {
+                quote! {
+                    if let ::std::task::Poll::Ready(output_val) =
+                        #future.as_mut().poll(ctx)
+                    {
+                        return ::std::task::Poll::Ready(
+                            #selected_enum_name::#variant(output_val),
+                        );
+                    }
+                }
+            });
+
+        let match_arms =
+            variant_names().zip(&self.arms).map(|(variant, arm)| {
+                let pat = &arm.pattern;
+                let final_output = &arm.output;
+                quote! {
+                    #selected_enum_name::#variant(#pat) => #final_output
+                }
+            });
+
+        quote! {
+            {
+                #(#future_decls)*
+                #selected_decl
+                let selected = ::std::future::poll_fn(|ctx| {
+                    use ::std::future::Future;
+                    #(#polls)*
+                    ::std::task::Poll::Pending
+                })
+                .await;
+                match selected {
+                    #(#match_arms),*
+                }
+            }
+        }
+    }
+}